@@ -4,7 +4,7 @@ use movingai::parser::parse_scen_file;
 use movingai::{MovingAiMap, SceneRecord};
 use std::path::Path;
 
-use blitz_path::{a_star_path, jps_path};
+use blitz_path::{a_star_path, jps_path, BitGrid};
 
 const MAP: &str = "./tests/map/maze512-32-9.map";
 const SCEN: &str = "./tests/map/maze512-32-9.map.scen";
@@ -12,6 +12,7 @@ const SCEN: &str = "./tests/map/maze512-32-9.map.scen";
 pub fn criterion_benchmark(c: &mut Criterion) {
     let mut group = c.benchmark_group("maze512-32-9");
     let (map, scenes) = load_files();
+    let grid = BitGrid::from_grid(&map);
     for i in [0, 34].iter() {
         let scene = scenes[*i as usize].clone();
         group.bench_with_input(BenchmarkId::new("A*", i), &scene, |b, scene| {
@@ -33,6 +34,16 @@ pub fn criterion_benchmark(c: &mut Criterion) {
                 )
             })
         });
+
+        group.bench_with_input(BenchmarkId::new("JPS BitGrid", i), &scene, |b, scene| {
+            b.iter(|| {
+                jps_path(
+                    black_box(&grid),
+                    black_box(scene.start_pos),
+                    black_box(scene.goal_pos),
+                )
+            })
+        });
     }
 
     group.finish();
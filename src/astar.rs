@@ -1,11 +1,15 @@
-use std::collections::BinaryHeap;
+use std::cmp::Ordering;
+use std::collections::hash_map::{Entry, HashMap};
+use std::hash::Hash;
+use std::time::Instant;
 
 use movingai::Coords2D;
-use movingai::Map2D;
 
+use crate::cost::{Cost, Fixed};
 use crate::node::Node;
-use crate::utils::{distance, rewind};
-use crate::Route;
+use crate::observer::SearchObserver;
+use crate::open::OpenList;
+use crate::{Grid, PathError, Route, SearchOptions, SearchStats, TieBreak};
 
 ///Creates a new route using the A* algorithm.
 ///Returns a Route struct containing the distance to the goal and number of steps needed to get there.
@@ -28,80 +32,500 @@ use crate::Route;
 /// }
 /// # Ok::<(), io::Error>(())
 /// ```
+pub fn a_star_path<G: Grid>(map: &G, start: Coords2D, goal: Coords2D) -> Option<Route> {
+    a_star_path_with(map, start, goal, &SearchOptions::default()).ok()
+}
 
-pub fn a_star_path<U, T: Map2D<U>>(map: &T, start: Coords2D, goal: Coords2D) -> Option<Route> {
-    if start == goal {
-        return Some(Route::from((0.0, vec![])));
+///Creates a new route using the A* algorithm with fixed-point costs.
+///Costs are measured in octile steps using integer arithmetic and nodes are queued in a radix heap,
+///which avoids floating-point comparisons and gives the same route on every platform.
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use std::path::Path;
+/// use blitz_path::Cost;
+///
+/// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+/// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+/// let scene = &scenes[0];
+///
+/// let path = blitz_path::a_star_path_fixed(&map, scene.start_pos, scene.goal_pos);
+///
+/// if let Some(path) = path {
+///     assert!((scene.optimal_length - path.distance().to_f64()).abs() < 1e-4);
+/// }
+/// # Ok::<(), io::Error>(())
+/// ```
+pub fn a_star_path_fixed<G: Grid>(
+    map: &G,
+    start: Coords2D,
+    goal: Coords2D,
+) -> Option<Route<Fixed>> {
+    a_star_path_with(map, start, goal, &SearchOptions::default()).ok()
+}
+
+///Creates a new route using the A* algorithm, configured by the given SearchOptions.
+///The distance of the route is measured using the Cost type `C`, which is usually inferred from the
+///type of Route expected.
+///Returns a PathError if there is no route to the goal or a cost used in the search is invalid.
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use std::path::Path;
+/// use blitz_path::{Route, SearchOptions, TieBreak};
+///
+/// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+/// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+/// let scene = &scenes[0];
+///
+/// let mut options = SearchOptions::default();
+/// options.tie_break = TieBreak::Position;
+/// let first: Route = blitz_path::a_star_path_with(&map, scene.start_pos, scene.goal_pos, &options)?;
+/// let second: Route = blitz_path::a_star_path_with(&map, scene.start_pos, scene.goal_pos, &options)?;
+///
+/// assert_eq!(first.steps(), second.steps());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn a_star_path_with<C: Cost, G: Grid>(
+    map: &G,
+    start: Coords2D,
+    goal: Coords2D,
+    options: &SearchOptions,
+) -> Result<Route<C>, PathError> {
+    grid_search(
+        map,
+        start,
+        goal,
+        options,
+        &mut SearchContext::new(),
+        &mut (),
+    )
+}
+
+///Creates a new route using the A* algorithm in the same way as [`a_star_path_with`], also returning
+///measures of the work done by the search, whether or not it found a route.
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use std::path::Path;
+/// use blitz_path::{Route, SearchOptions};
+///
+/// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+/// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+/// let scene = &scenes[0];
+///
+/// let options = SearchOptions::default();
+/// let (path, stats) = blitz_path::a_star_path_with_stats(&map, scene.start_pos, scene.goal_pos, &options);
+/// let path: Route = path?;
+///
+/// assert!(stats.expanded >= path.len() - 1);
+/// assert!(stats.generated >= stats.expanded);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn a_star_path_with_stats<C: Cost, G: Grid>(
+    map: &G,
+    start: Coords2D,
+    goal: Coords2D,
+    options: &SearchOptions,
+) -> (Result<Route<C>, PathError>, SearchStats) {
+    let mut context = SearchContext::new();
+    let timer = Instant::now();
+    let result = grid_search(map, start, goal, options, &mut context, &mut ());
+    let stats = SearchStats {
+        elapsed: timer.elapsed(),
+        ..context.stats
+    };
+
+    (result, stats)
+}
+
+///Creates a new route using the A* algorithm in the same way as [`a_star_path_with`], reporting each
+///step of the search to an observer.
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use std::path::Path;
+/// use blitz_path::{Coords2D, Route, SearchObserver, SearchOptions};
+///
+/// //Records the order tiles are expanded in
+/// struct Frontier(Vec<Coords2D>);
+///
+/// impl SearchObserver for Frontier {
+///     fn expanded(&mut self, node: Coords2D) {
+///         self.0.push(node);
+///     }
+/// }
+///
+/// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+/// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+/// let scene = &scenes[0];
+///
+/// let mut frontier = Frontier(Vec::new());
+/// let options = SearchOptions::default();
+/// let path: Route = blitz_path::a_star_path_observed(&map, scene.start_pos, scene.goal_pos, &options, &mut frontier)?;
+///
+/// assert_eq!(frontier.0.first(), Some(&scene.start_pos));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[cfg(feature = "observer")]
+pub fn a_star_path_observed<C: Cost, G: Grid, O: SearchObserver>(
+    map: &G,
+    start: Coords2D,
+    goal: Coords2D,
+    options: &SearchOptions,
+    observer: &mut O,
+) -> Result<Route<C>, PathError> {
+    grid_search(
+        map,
+        start,
+        goal,
+        options,
+        &mut SearchContext::new(),
+        observer,
+    )
+}
+
+//A* over a grid, moving to each tile's neighbours
+fn grid_search<C: Cost, G: Grid, O: SearchObserver>(
+    map: &G,
+    start: Coords2D,
+    goal: Coords2D,
+    options: &SearchOptions,
+    context: &mut SearchContext<C>,
+    observer: &mut O,
+) -> Result<Route<C>, PathError> {
+    let graph = Graph {
+        successors: |position, successors: &mut Vec<(Coords2D, C)>| {
+            for successor in map.neighbours(position) {
+                //Weight distances by the cost of the tile being moved into
+                let tile_cost = map.cost(successor);
+                if !(tile_cost.is_finite() && tile_cost >= 0.0) {
+                    return Err(PathError::InvalidCost);
+                }
+
+                successors.push((successor, C::distance(position, successor).scale(tile_cost)));
+            }
+
+            Ok(())
+        },
+        heuristic: |position| C::distance(position, goal),
+        order: Coords2D::cmp,
+    };
+
+    search(start, goal, graph, options, context, observer)
+}
+
+///Creates a new route between two nodes of any graph using the A* algorithm.
+///
+///`successors` returns each node which can be reached in a single step from a node, along with the
+///cost of that step. `heuristic` estimates the cost of travelling from a node to the goal, and must
+///never overestimate it for the route to be the shortest. Steps in the returned Route are the graph's
+///nodes, with the goal at index 0.
+///Returns a PathError if there is no route to the goal or a cost used in the search is invalid.
+/// # Examples
+///
+/// ```
+/// use blitz_path::{Route, SearchOptions};
+///
+/// //A small road network, with the cost of each road
+/// let roads = [("a", "b", 4), ("a", "c", 1), ("c", "b", 2), ("b", "d", 5), ("c", "d", 8)];
+///
+/// let route: Route<u32, &str> = blitz_path::a_star(
+///     "a",
+///     "d",
+///     |town| roads.iter().filter(move |road| road.0 == town).map(|road| (road.1, road.2)),
+///     |_| 0,
+///     &SearchOptions::default(),
+/// )?;
+///
+/// assert_eq!(route.distance(), 8);
+/// assert_eq!(route.steps(), vec!["d", "b", "c", "a"]);
+/// # Ok::<(), blitz_path::PathError>(())
+/// ```
+pub fn a_star<N, C, FN, IN, FH>(
+    start: N,
+    goal: N,
+    mut successors: FN,
+    heuristic: FH,
+    options: &SearchOptions,
+) -> Result<Route<C, N>, PathError>
+where
+    N: Eq + Hash + Copy,
+    C: Cost,
+    FN: FnMut(N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FH: FnMut(N) -> C,
+{
+    let graph = Graph {
+        successors: |node, buffer: &mut Vec<(N, C)>| {
+            buffer.extend(successors(node));
+            Ok(())
+        },
+        heuristic,
+        //Nodes need not be ordered, so ties fall back to the order nodes were discovered in
+        order: |_: &N, _: &N| Ordering::Equal,
+    };
+
+    search(
+        start,
+        goal,
+        graph,
+        options,
+        &mut SearchContext::new(),
+        &mut (),
+    )
+}
+
+///Storage used by A*, which can be kept between searches to avoid allocating it again.
+///
+///Each search clears the context before it starts, so a context can be reused for any number of
+///searches on any grid, but only by one search at a time. [`find_paths`](crate::find_paths) creates a
+///context for each batch of queries it runs.
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use std::path::Path;
+/// use blitz_path::{Route, SearchContext, SearchOptions};
+///
+/// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+/// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+///
+/// let mut context = SearchContext::new();
+/// let options = SearchOptions::default();
+///
+/// for scene in scenes.iter().take(10) {
+///     let path: Route = context.a_star_path(&map, scene.start_pos, scene.goal_pos, &options)?;
+///     assert_eq!(scene.optimal_length as f32, path.distance() as f32);
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct SearchContext<C = f64, N = Coords2D> {
+    open: OpenList<usize, C>,
+    indices: HashMap<N, usize>,
+    records: Vec<Record<N, C>>,
+    buffer: Vec<(N, C)>,
+    stats: SearchStats,
+}
+
+impl<C: Cost, N> SearchContext<C, N> {
+    ///Creates a new, empty context.
+    pub fn new() -> SearchContext<C, N> {
+        SearchContext {
+            open: OpenList::with_capacity(64, TieBreak::default()),
+            indices: HashMap::new(),
+            records: Vec::new(),
+            buffer: Vec::new(),
+            stats: SearchStats::default(),
+        }
+    }
+}
+
+impl<C: Cost, N> Default for SearchContext<C, N> {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    //Initialize open and closed lists
-    let capacity = (1 + start.0 + 1 - start.0 - 1) * (1 + start.1 + 1 - start.1 - 1);
-    let mut open = BinaryHeap::with_capacity(capacity);
-    let mut closed = Vec::<Node>::with_capacity(capacity);
+impl<C: Cost> SearchContext<C> {
+    ///Creates a new route using the A* algorithm in the same way as [`a_star_path_with`], reusing the
+    ///context's storage.
+    pub fn a_star_path<G: Grid>(
+        &mut self,
+        map: &G,
+        start: Coords2D,
+        goal: Coords2D,
+        options: &SearchOptions,
+    ) -> Result<Route<C>, PathError> {
+        grid_search(map, start, goal, options, self, &mut ())
+    }
+}
+
+//Best known route to a node discovered by the search
+#[derive(Debug)]
+struct Record<N, C> {
+    node: N,
+    g: C,
+    h: C,
+    parent: usize,
+    closed: bool,
+}
+
+//How A* moves between the nodes of a graph
+struct Graph<FS, FH, FO> {
+    //Adds each node reachable in a single step, with the cost of the step
+    successors: FS,
+    //Estimates the cost from a node to the goal
+    heuristic: FH,
+    //Orders nodes for TieBreak::Position
+    order: FO,
+}
+
+//A* over any graph. Nodes are numbered in the order they are discovered, so the open list only
+//needs to compare numbers and ties are always broken in the same order.
+fn search<N, C, FS, FH, FO, O>(
+    start: N,
+    goal: N,
+    graph: Graph<FS, FH, FO>,
+    options: &SearchOptions,
+    context: &mut SearchContext<C, N>,
+    observer: &mut O,
+) -> Result<Route<C, N>, PathError>
+where
+    N: Eq + Hash + Copy,
+    C: Cost,
+    FS: FnMut(N, &mut Vec<(N, C)>) -> Result<(), PathError>,
+    FH: FnMut(N) -> C,
+    FO: Fn(&N, &N) -> Ordering,
+    O: SearchObserver<N>,
+{
+    let Graph {
+        mut successors,
+        mut heuristic,
+        order,
+    } = graph;
+
+    //Clear anything left from the last search to use the context
+    let SearchContext {
+        open,
+        indices,
+        records,
+        buffer,
+        stats,
+    } = context;
+    open.clear();
+    open.set_tie_break(options.tie_break);
+    indices.clear();
+    records.clear();
+    *stats = SearchStats::default();
+
+    if start == goal {
+        return Ok(Route::from((C::zero(), vec![])));
+    }
 
     //Push start node to open list
-    open.push(Node {
-        f: distance(start, goal),
-        g: 0.0,
-        h: distance(start, goal),
-        position: start,
-        parent: start,
+    let start_node = Node::new(C::zero(), heuristic(start), 0, 0);
+    if !start_node.is_valid() {
+        return Err(PathError::InvalidCost);
+    }
+    indices.insert(start, 0);
+    records.push(Record {
+        node: start,
+        g: start_node.g,
+        h: start_node.h,
+        parent: 0,
+        closed: false,
     });
+    open.push_by(start_node, &|a: &usize, b: &usize| {
+        order(&records[*a].node, &records[*b].node)
+    });
+    observer.pushed(start, start, 0.0, start_node.h.to_f64());
+    stats.generated += 1;
+    stats.max_open = 1;
 
     //Examine the nodes
-    while let Some(node_current) = open.pop() {
+    while let Some(node_current) =
+        open.pop_by(&|a: &usize, b: &usize| order(&records[*a].node, &records[*b].node))
+    {
+        let current = node_current.position;
+
+        //Skip nodes which have already been expanded or have since been reached by a shorter route
+        let record = &mut records[current];
+        if record.closed || node_current.g.total_cmp(&record.g) == Ordering::Greater {
+            continue;
+        }
+        record.closed = true;
+
         //If this is the target node return the distance to get there
-        if node_current.position == goal {
-            let path = rewind(&node_current, &closed);
-            let route = Route::from((node_current.g, path));
-            return Some(route);
+        if record.node == goal {
+            let path = rewind(current, records);
+            observer.found(&path, node_current.g.to_f64());
+            return Ok(Route::from((node_current.g, path)));
         }
+        stats.expanded += 1;
+        observer.expanded(record.node);
 
         //Setup successor nodes
-        for successor in map.neighbors(node_current.position) {
-            //Calculate distances
-            let distance_to_goal = distance(successor, goal);
-            let distance_from_parent = distance(node_current.position, successor);
-            let total_distance = node_current.g + distance_from_parent;
-
-            //Check if node is on closed list
-            if let Some(closed) = closed.iter().find(|x| x.position == successor) {
-                //and skip if existing score is better
-                if closed.f < total_distance + distance_to_goal {
-                    continue;
-                }
-            }
+        buffer.clear();
+        successors(record.node, buffer)?;
 
-            //See if node is on open list
-            if let Some(old_open) = open.iter().find(|x| x.position == successor) {
-                //Continue if we already have a better result
-                if old_open.g < total_distance {
-                    continue;
-                }
+        for (successor, step) in buffer.drain(..) {
+            if !step.is_valid() {
+                return Err(PathError::InvalidCost);
             }
+            let total_distance = node_current.g.plus(step);
+
+            let index = match indices.entry(successor) {
+                Entry::Occupied(entry) => {
+                    //Continue if we already have a better result, otherwise reopen the node
+                    let index = *entry.get();
+                    let record = &mut records[index];
+                    if record.g <= total_distance {
+                        continue;
+                    }
 
-            //If sill best distance build node and push to open list
-            //Build node from distances
-            let node_successor = Node {
-                f: total_distance + distance_to_goal,
-                g: total_distance,
-                h: distance_to_goal,
-                position: successor,
-                parent: node_current.position,
+                    if record.closed {
+                        stats.reopened += 1;
+                    }
+                    record.g = total_distance;
+                    record.parent = current;
+                    record.closed = false;
+                    index
+                }
+                Entry::Vacant(entry) => {
+                    let index = records.len();
+                    entry.insert(index);
+                    records.push(Record {
+                        node: successor,
+                        g: total_distance,
+                        h: heuristic(successor),
+                        parent: current,
+                        closed: false,
+                    });
+                    index
+                }
             };
 
-            open.push(node_successor);
-        }
+            //Build node from distances and push to open list
+            let node_successor = Node::new(total_distance, records[index].h, index, current);
+            if !node_successor.is_valid() {
+                return Err(PathError::InvalidCost);
+            }
 
-        //Push current node to closed list and remove existing entry if duplicate
-        if let Some(index) = closed
-            .iter()
-            .position(|x| x.position == node_current.position)
-        {
-            closed.remove(index);
+            open.push_by(node_successor, &|a: &usize, b: &usize| {
+                order(&records[*a].node, &records[*b].node)
+            });
+            observer.pushed(
+                successor,
+                records[current].node,
+                total_distance.to_f64(),
+                records[index].h.to_f64(),
+            );
+            stats.generated += 1;
+            stats.max_open = stats.max_open.max(open.len());
         }
-        closed.push(node_current);
+        observer.closed(records[current].node);
     }
 
-    None
+    Err(PathError::NoPath)
+}
+
+//Helper function to recreate path once goal is located
+fn rewind<N: Copy, C>(goal: usize, records: &[Record<N, C>]) -> Vec<N> {
+    let mut path = Vec::new();
+    let mut index = goal;
+
+    loop {
+        path.push(records[index].node);
+
+        if index == 0 {
+            return path;
+        }
+        index = records[index].parent;
+    }
 }
@@ -0,0 +1,67 @@
+use movingai::Coords2D;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{Cost, Grid, PathError, Route, SearchContext, SearchOptions};
+
+///Finds a route for each pair of start and goal tiles using the A* algorithm, returning the routes in
+///the same order as the queries.
+///Each route is None if there is no route between its start and goal, as with
+///[`a_star_path`](crate::a_star_path).
+///
+///With the `rayon` feature enabled the queries are split into jobs shared between rayon's threads, with
+///each job creating a [`SearchContext`] and reusing it for all of its queries. Rayon may split the
+///queries into more jobs than there are threads, so a thread can create several contexts. Otherwise
+///the queries are run one after another, reusing a single context.
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use std::path::Path;
+///
+/// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+/// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+///
+/// let queries: Vec<_> = scenes.iter().take(10).map(|x| (x.start_pos, x.goal_pos)).collect();
+/// let paths = blitz_path::find_paths(&map, &queries);
+///
+/// for (scene, path) in scenes.iter().zip(paths) {
+///     assert_eq!(scene.optimal_length as f32, path.unwrap().distance() as f32);
+/// }
+/// # Ok::<(), io::Error>(())
+/// ```
+pub fn find_paths<G: Grid + Sync>(map: &G, queries: &[(Coords2D, Coords2D)]) -> Vec<Option<Route>> {
+    find_paths_with(map, queries, &SearchOptions::default())
+        .into_iter()
+        .map(Result::ok)
+        .collect()
+}
+
+///Finds a route for each pair of start and goal tiles in the same way as [`find_paths`], configured by
+///the given SearchOptions.
+///The distance of each route is measured using the Cost type `C`, and each result is a PathError if
+///there is no route or a cost is invalid, as with [`a_star_path_with`](crate::a_star_path_with).
+pub fn find_paths_with<C: Cost + Send, G: Grid + Sync>(
+    map: &G,
+    queries: &[(Coords2D, Coords2D)],
+    options: &SearchOptions,
+) -> Vec<Result<Route<C>, PathError>> {
+    #[cfg(feature = "rayon")]
+    {
+        queries
+            .par_iter()
+            .map_init(SearchContext::new, |context, &(start, goal)| {
+                context.a_star_path(map, start, goal, options)
+            })
+            .collect()
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        let mut context = SearchContext::new();
+        queries
+            .iter()
+            .map(|&(start, goal)| context.a_star_path(map, start, goal, options))
+            .collect()
+    }
+}
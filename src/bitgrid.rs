@@ -0,0 +1,248 @@
+use movingai::Coords2D;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+
+use crate::Grid;
+
+///A grid which stores whether each tile is passable as a single bit.
+///
+///Tiles are stored both row by row and column by column, allowing JPS to scan along rows and columns
+///64 tiles at a time using bitwise operations, rather than checking each tile in turn.
+///
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use std::path::Path;
+/// use blitz_path::BitGrid;
+///
+/// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+/// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+/// let scene = &scenes[0];
+///
+/// let grid = BitGrid::from_grid(&map);
+/// let path = blitz_path::jps_path(&grid, scene.start_pos, scene.goal_pos);
+///
+/// if let Some(path) = path {
+///     assert_eq!(scene.optimal_length as f32, path.distance() as f32);
+/// }
+/// # Ok::<(), io::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "BitGridData"))]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    rows: Bits,
+    columns: Bits,
+}
+
+//A BitGrid as loaded, before checking its rows and columns hold the same tiles
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct BitGridData {
+    width: usize,
+    height: usize,
+    rows: Bits,
+    columns: Bits,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<BitGridData> for BitGrid {
+    type Error = String;
+
+    fn try_from(data: BitGridData) -> Result<Self, Self::Error> {
+        //Check the sizes before allocating anything
+        let words = |lines: usize, length: usize| lines.checked_mul(length.div_ceil(64));
+        if words(data.height, data.width) != Some(data.rows.words.len())
+            || words(data.width, data.height) != Some(data.columns.words.len())
+        {
+            return Err(String::from("bit grid has the wrong number of words"));
+        }
+        let mut grid = BitGrid::new(data.width, data.height);
+
+        //Rebuild the grid from its rows, so the columns and any padding must match
+        let rows = Bits {
+            words: data.rows.words,
+            ..grid.rows.clone()
+        };
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                grid.set_passable((x, y), rows.get(y, x));
+            }
+        }
+        if grid.rows != rows || grid.columns.words != data.columns.words {
+            return Err(String::from("bit grid rows and columns do not match"));
+        }
+
+        Ok(grid)
+    }
+}
+
+impl BitGrid {
+    ///Creates a new grid where every tile is impassable.
+    pub fn new(width: usize, height: usize) -> BitGrid {
+        BitGrid {
+            width,
+            height,
+            rows: Bits::new(height, width),
+            columns: Bits::new(width, height),
+        }
+    }
+
+    ///Creates a new grid with the same passable tiles as another grid.
+    ///Tile costs and any other rules about which moves are allowed are not copied.
+    pub fn from_grid<G: Grid>(grid: &G) -> BitGrid {
+        let mut bit_grid = BitGrid::new(grid.width(), grid.height());
+
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                bit_grid.set_passable((x, y), grid.is_passable((x, y)));
+            }
+        }
+
+        bit_grid
+    }
+
+    ///Sets whether a tile is passable.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tile is outside the grid.
+    pub fn set_passable(&mut self, tile: Coords2D, passable: bool) {
+        assert!(
+            tile.0 < self.width && tile.1 < self.height,
+            "tile {:?} is outside the grid",
+            tile
+        );
+
+        self.rows.set(tile.1, tile.0, passable);
+        self.columns.set(tile.0, tile.1, passable);
+    }
+}
+
+impl Grid for BitGrid {
+    #[inline]
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    #[inline]
+    fn is_passable(&self, tile: Coords2D) -> bool {
+        tile.0 < self.width && tile.1 < self.height && self.rows.get(tile.1, tile.0)
+    }
+
+    fn scan(&self, tile: Coords2D, direction: (i32, i32)) -> Coords2D {
+        match direction {
+            (dx, 0) if dx != 0 => {
+                let x = self.rows.scan(tile.1 as isize, tile.0 as isize, dx);
+                (x as usize, tile.1)
+            }
+            (0, dy) if dy != 0 => {
+                let y = self.columns.scan(tile.0 as isize, tile.1 as isize, dy);
+                (tile.0, y as usize)
+            }
+            _ => tile,
+        }
+    }
+}
+
+//Bits for a set of lines, with each line padded to a whole number of words
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Bits {
+    lines: usize,
+    stride: usize,
+    words: Vec<u64>,
+}
+
+impl Bits {
+    fn new(lines: usize, length: usize) -> Bits {
+        let stride = length.div_ceil(64);
+
+        Bits {
+            lines,
+            stride,
+            words: vec![0; lines * stride],
+        }
+    }
+
+    #[inline]
+    fn get(&self, line: usize, position: usize) -> bool {
+        self.words[line * self.stride + position / 64] & (1 << (position % 64)) != 0
+    }
+
+    fn set(&mut self, line: usize, position: usize, value: bool) {
+        let word = &mut self.words[line * self.stride + position / 64];
+        if value {
+            *word |= 1 << (position % 64);
+        } else {
+            *word &= !(1 << (position % 64));
+        }
+    }
+
+    //Returns the word at an index in a line, or zero if outside the grid
+    #[inline]
+    fn word(&self, line: isize, index: isize) -> u64 {
+        if line < 0 || line as usize >= self.lines || index < 0 || index as usize >= self.stride {
+            0
+        } else {
+            self.words[line as usize * self.stride + index as usize]
+        }
+    }
+
+    //Returns 64 bits of a line, with bit i being the tile at start + i
+    #[inline]
+    fn window(&self, line: isize, start: isize) -> u64 {
+        let index = start.div_euclid(64);
+        let offset = start.rem_euclid(64) as u32;
+
+        if offset == 0 {
+            self.word(line, index)
+        } else {
+            (self.word(line, index) >> offset) | (self.word(line, index + 1) << (64 - offset))
+        }
+    }
+
+    //Finds the first position, moving along a line from start, which is blocked or has a forced
+    //neighbour on one of the lines either side of it
+    fn scan(&self, line: isize, start: isize, direction: i32) -> isize {
+        let mut position = start;
+
+        loop {
+            if direction > 0 {
+                let stops = self.stops(line, position, position + 1);
+                if stops != 0 {
+                    return position + stops.trailing_zeros() as isize;
+                }
+
+                position += 64;
+            } else {
+                let stops = self.stops(line, position - 63, position - 64);
+                if stops != 0 {
+                    return position - stops.leading_zeros() as isize;
+                }
+
+                position -= 64;
+            }
+        }
+    }
+
+    //Tiles which are blocked, or are next to a blocked tile that has a passable tile after it
+    #[inline]
+    fn stops(&self, line: isize, start: isize, next: isize) -> u64 {
+        let blocked = !self.window(line, start);
+        let before = !self.window(line - 1, start) & self.window(line - 1, next);
+        let after = !self.window(line + 1, start) & self.window(line + 1, next);
+
+        blocked | before | after
+    }
+}
@@ -0,0 +1,231 @@
+use std::convert::TryFrom;
+
+use movingai::Coords2D;
+
+use crate::{Cost, Grid, Route};
+
+//Directions which segments can move in, numbered for encoding
+const DIRECTIONS: [(i32, i32); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+///A straight part of a [`CompactRoute`], moving a number of tiles in one of eight directions.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Segment {
+    //Index into DIRECTIONS, so a segment can only hold one of the eight directions
+    direction: u8,
+    run: u32,
+}
+
+impl Segment {
+    ///Returns the step taken each move, with x and y each being -1, 0 or 1.
+    pub fn direction(&self) -> (i32, i32) {
+        DIRECTIONS[self.direction as usize]
+    }
+
+    ///Returns the number of moves made in the direction.
+    pub fn run(&self) -> u32 {
+        self.run
+    }
+}
+
+///A route stored as its start and the straight segments leading from there to the goal.
+///
+///Routes which move one tile at a time, such as those found by A* and JPS, can be compressed into a
+///few segments, and encoded in a few bytes to send over a network or store in a save file.
+///
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use std::path::Path;
+/// use blitz_path::{CompactRoute, Route};
+///
+/// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+/// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+/// let scene = &scenes[0];
+///
+/// if let Some(path) = blitz_path::a_star_path(&map, scene.start_pos, scene.goal_pos) {
+///     let bytes = path.compress().unwrap().to_bytes();
+///     let route: Route = CompactRoute::from_bytes(&bytes, &map).unwrap().to_route();
+///
+///     assert_eq!(route.as_slice(), path.as_slice());
+/// }
+/// # Ok::<(), io::Error>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CompactRoute {
+    start: Option<Coords2D>,
+    segments: Vec<Segment>,
+}
+
+impl CompactRoute {
+    ///Returns the first step of the route, or None if it is empty.
+    pub fn start(&self) -> Option<Coords2D> {
+        self.start
+    }
+
+    ///Returns the segments leading from the start to the goal.
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    ///Expands the route into a Route containing every step, with the distance measured using the Cost
+    ///type `C`. The distance does not include the costs of any tiles.
+    pub fn to_route<C: Cost>(&self) -> Route<C> {
+        let mut steps = Vec::new();
+        let mut distance = C::zero();
+
+        if let Some(start) = self.start {
+            steps.push(start);
+
+            for segment in self.segments.iter() {
+                let from = steps[steps.len() - 1];
+                for _ in 0..segment.run {
+                    let last = steps[steps.len() - 1];
+                    steps.push(shift(last, segment.direction()));
+                }
+                distance = distance.plus(C::distance(from, steps[steps.len() - 1]));
+            }
+        }
+
+        steps.reverse();
+        Route::from((distance, steps))
+    }
+
+    ///Encodes the route as bytes, using a variable length number for the start and for each segment.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 + self.segments.len() * 2);
+
+        if let Some(start) = self.start {
+            write_number(&mut bytes, start.0 as u64);
+            write_number(&mut bytes, start.1 as u64);
+
+            for segment in self.segments.iter() {
+                write_number(
+                    &mut bytes,
+                    (segment.run as u64) << 3 | segment.direction as u64,
+                );
+            }
+        }
+
+        bytes
+    }
+
+    ///Decodes a route encoded by [`to_bytes`](CompactRoute::to_bytes). Returns None if the bytes are not
+    ///a valid route, including if the route would start or move off the edge of the map, or would make
+    ///more moves than the map has tiles. A route which visits each tile at most once never makes that
+    ///many moves, and the limit keeps [`to_route`](CompactRoute::to_route) from expanding a few bytes
+    ///into a huge number of steps.
+    pub fn from_bytes<G: Grid>(bytes: &[u8], map: &G) -> Option<CompactRoute> {
+        let mut bytes = bytes.iter();
+        let mut route = CompactRoute::default();
+
+        if bytes.len() == 0 {
+            return Some(route);
+        }
+
+        let x = usize::try_from(read_number(&mut bytes)?).ok()?;
+        let y = usize::try_from(read_number(&mut bytes)?).ok()?;
+        let on_map = |x: i64, y: i64| {
+            x >= 0 && y >= 0 && (x as u64) < map.width() as u64 && (y as u64) < map.height() as u64
+        };
+        let (mut x, mut y) = (i64::try_from(x).ok()?, i64::try_from(y).ok()?);
+        if !on_map(x, y) {
+            return None;
+        }
+        route.start = Some((x as usize, y as usize));
+
+        let limit = (map.width() as u64).saturating_mul(map.height() as u64);
+        let mut moves = 0u64;
+        while bytes.len() > 0 {
+            let value = read_number(&mut bytes)?;
+            let segment = Segment {
+                direction: (value & 7) as u8,
+                run: u32::try_from(value >> 3).ok().filter(|run| *run > 0)?,
+            };
+
+            //Check the segment stays on the map
+            let (dx, dy) = segment.direction();
+            x = x.checked_add(dx as i64 * segment.run as i64)?;
+            y = y.checked_add(dy as i64 * segment.run as i64)?;
+            if !on_map(x, y) {
+                return None;
+            }
+
+            moves += segment.run as u64;
+            if moves > limit {
+                return None;
+            }
+
+            route.segments.push(segment);
+        }
+
+        Some(route)
+    }
+}
+
+impl<C> Route<C> {
+    ///Compresses the route into its start and the straight segments leading to the goal.
+    ///Returns None if any two consecutive steps are not neighbouring tiles, such as in a simplified route.
+    pub fn compress(&self) -> Option<CompactRoute> {
+        let mut route = CompactRoute {
+            start: self.iter_forward().next().copied(),
+            segments: Vec::new(),
+        };
+
+        let mut steps = self.iter_forward().peekable();
+        while let (Some(from), Some(to)) = (steps.next(), steps.peek()) {
+            let direction = (to.0 as i64 - from.0 as i64, to.1 as i64 - from.1 as i64);
+            if direction == (0, 0) || direction.0.abs() > 1 || direction.1.abs() > 1 {
+                return None;
+            }
+            let direction = (direction.0 as i32, direction.1 as i32);
+            let direction = DIRECTIONS.iter().position(|x| *x == direction)? as u8;
+
+            match route.segments.last_mut() {
+                Some(last) if last.direction == direction && last.run < u32::MAX => last.run += 1,
+                _ => route.segments.push(Segment { direction, run: 1 }),
+            }
+        }
+
+        Some(route)
+    }
+}
+
+fn shift(tile: Coords2D, direction: (i32, i32)) -> Coords2D {
+    (
+        (tile.0 as i64 + direction.0 as i64) as usize,
+        (tile.1 as i64 + direction.1 as i64) as usize,
+    )
+}
+
+//Writes a number seven bits at a time, with the top bit of each byte set if more follow
+fn write_number(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_number<'a>(bytes: &mut impl Iterator<Item = &'a u8>) -> Option<u64> {
+    let mut value = 0u64;
+
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.next()?;
+        value |= ((byte & 0x7f) as u64).checked_shl(shift)?;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+
+    None
+}
@@ -0,0 +1,323 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::ops::{Add, Sub};
+
+use movingai::Coords2D;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::utils::distance;
+
+pub(crate) mod sealed {
+    ///Arithmetic used inside the searches, kept out of the public Cost interface.
+    pub trait Arithmetic: Sized {
+        ///Adds two costs. Integer costs saturate to an invalid cost instead of overflowing.
+        fn plus(self, other: Self) -> Self;
+
+        ///Multiplies the cost by a factor, such as the cost of a tile on a Grid. Integer costs
+        ///which can not hold the result become invalid.
+        fn scale(self, factor: f64) -> Self;
+
+        ///The largest relative rounding error of a single addition, which is zero for integer costs.
+        fn epsilon() -> f64 {
+            0.0
+        }
+
+        ///Integer costs return a key which allows them to be queued in a radix heap,
+        ///which is faster than a binary heap but requires keys to never decrease.
+        ///Keys must be ordered in the same way as the costs they are created from.
+        fn radix_key(self) -> Option<u64> {
+            None
+        }
+    }
+}
+
+///A numeric type used by the search algorithms to measure the length of a path.
+///
+///Implemented for `f32` and `f64`, which measure Euclidean distances, and for `u32` and [`Fixed`],
+///which measure octile distances using integer arithmetic. `u32` costs use the common scale of 10 for
+///a straight step and 14 for a diagonal step. Integer costs too large to store are invalid, so a
+///search which overflows returns [`PathError::InvalidCost`](crate::PathError::InvalidCost).
+///
+///This trait is sealed and can not be implemented outside of this crate.
+pub trait Cost: Copy + PartialOrd + Add<Output = Self> + sealed::Arithmetic {
+    ///The cost of a path with no steps.
+    fn zero() -> Self;
+
+    ///The cost of travelling in a straight line between two tiles.
+    fn distance(a: Coords2D, b: Coords2D) -> Self;
+
+    ///Converts the cost into an f64.
+    fn to_f64(self) -> f64;
+
+    ///Compares two costs, giving a total order even for invalid costs.
+    fn total_cmp(&self, other: &Self) -> Ordering;
+
+    ///Whether the cost can be used in a search, costs must be finite and not negative.
+    fn is_valid(self) -> bool;
+}
+
+impl Cost for f64 {
+    #[inline]
+    fn zero() -> Self {
+        0.0
+    }
+
+    #[inline]
+    fn distance(a: Coords2D, b: Coords2D) -> Self {
+        distance(a, b)
+    }
+
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    #[inline]
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f64::total_cmp(self, other)
+    }
+
+    #[inline]
+    fn is_valid(self) -> bool {
+        self.is_finite() && self >= 0.0
+    }
+}
+
+impl sealed::Arithmetic for f64 {
+    #[inline]
+    fn plus(self, other: Self) -> Self {
+        self + other
+    }
+
+    #[inline]
+    fn epsilon() -> f64 {
+        f64::EPSILON
+    }
+
+    #[inline]
+    fn scale(self, factor: f64) -> Self {
+        self * factor
+    }
+}
+
+impl Cost for f32 {
+    #[inline]
+    fn zero() -> Self {
+        0.0
+    }
+
+    #[inline]
+    fn distance(a: Coords2D, b: Coords2D) -> Self {
+        let (x, y) = (a.0 as f32, a.1 as f32);
+        let (p, q) = (b.0 as f32, b.1 as f32);
+        ((x - p) * (x - p) + (y - q) * (y - q)).sqrt()
+    }
+
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    #[inline]
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f32::total_cmp(self, other)
+    }
+
+    #[inline]
+    fn is_valid(self) -> bool {
+        self.is_finite() && self >= 0.0
+    }
+}
+
+impl sealed::Arithmetic for f32 {
+    #[inline]
+    fn plus(self, other: Self) -> Self {
+        self + other
+    }
+
+    #[inline]
+    fn epsilon() -> f64 {
+        f32::EPSILON as f64
+    }
+
+    #[inline]
+    fn scale(self, factor: f64) -> Self {
+        self * factor as f32
+    }
+}
+
+impl Cost for u32 {
+    #[inline]
+    fn zero() -> Self {
+        0
+    }
+
+    #[inline]
+    fn distance(a: Coords2D, b: Coords2D) -> Self {
+        let (diagonal, straight) = octile(a, b);
+
+        u32::try_from(diagonal * 14 + straight * 10).unwrap_or(u32::MAX)
+    }
+
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self as f64 / 10.0
+    }
+
+    #[inline]
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+
+    #[inline]
+    fn is_valid(self) -> bool {
+        self != u32::MAX
+    }
+}
+
+impl sealed::Arithmetic for u32 {
+    #[inline]
+    fn plus(self, other: Self) -> Self {
+        self.saturating_add(other)
+    }
+
+    #[inline]
+    fn scale(self, factor: f64) -> Self {
+        scale_bits(self as u64, factor, u32::MAX as u64) as u32
+    }
+
+    #[inline]
+    fn radix_key(self) -> Option<u64> {
+        Some(self as u64)
+    }
+}
+
+///A fixed-point cost with 16 fractional bits.
+///
+///Distances are measured in octile steps, with a straight step costing exactly one and a diagonal
+///step costing the closest fixed-point value to the square root of two. Distances are added using
+///integer arithmetic, and tile costs other than one are applied with a single correctly rounded
+///multiplication, so searches using fixed-point costs give identical results on every platform.
+///The largest value is invalid, and is the result of any overflow.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Fixed(u64);
+
+impl Fixed {
+    ///Number of bits used to store the fractional part of the cost.
+    pub const FRACTIONAL_BITS: u32 = 16;
+
+    ///The cost of a single straight step.
+    pub const ONE: Fixed = Fixed(1 << Self::FRACTIONAL_BITS);
+
+    ///The cost of a single diagonal step.
+    pub const SQRT_2: Fixed = Fixed(92_682);
+
+    ///Creates a cost from its raw fixed-point representation.
+    pub const fn from_bits(bits: u64) -> Fixed {
+        Fixed(bits)
+    }
+
+    ///Returns the raw fixed-point representation of the cost.
+    pub const fn to_bits(self) -> u64 {
+        self.0
+    }
+}
+
+impl Add for Fixed {
+    type Output = Fixed;
+
+    #[inline]
+    fn add(self, other: Fixed) -> Fixed {
+        Fixed(self.0.saturating_add(other.0))
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+
+    #[inline]
+    fn sub(self, other: Fixed) -> Fixed {
+        Fixed(self.0.saturating_sub(other.0))
+    }
+}
+
+impl Cost for Fixed {
+    #[inline]
+    fn zero() -> Self {
+        Fixed(0)
+    }
+
+    #[inline]
+    fn distance(a: Coords2D, b: Coords2D) -> Self {
+        let (diagonal, straight) = octile(a, b);
+
+        let bits = diagonal
+            .checked_mul(Self::SQRT_2.0)
+            .zip(straight.checked_mul(Self::ONE.0))
+            .and_then(|(diagonal, straight)| diagonal.checked_add(straight));
+
+        Fixed(bits.unwrap_or(u64::MAX))
+    }
+
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self.0 as f64 / Self::ONE.0 as f64
+    }
+
+    #[inline]
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+
+    #[inline]
+    fn is_valid(self) -> bool {
+        self.0 != u64::MAX
+    }
+}
+
+impl sealed::Arithmetic for Fixed {
+    #[inline]
+    fn plus(self, other: Self) -> Self {
+        self + other
+    }
+
+    #[inline]
+    fn scale(self, factor: f64) -> Self {
+        Fixed(scale_bits(self.0, factor, u64::MAX))
+    }
+
+    #[inline]
+    fn radix_key(self) -> Option<u64> {
+        Some(self.0)
+    }
+}
+
+//Scales an integer cost, returning `max` for invalid costs, invalid factors and overflow
+#[inline]
+fn scale_bits(bits: u64, factor: f64, max: u64) -> u64 {
+    if factor == 1.0 || bits == max {
+        return bits;
+    }
+
+    let scaled = (bits as f64 * factor).round();
+    if scaled >= 0.0 && scaled < max as f64 {
+        scaled as u64
+    } else {
+        max
+    }
+}
+
+//Splits the distance between two tiles into diagonal and straight steps
+#[inline]
+fn octile(a: Coords2D, b: Coords2D) -> (u64, u64) {
+    let dx = (a.0 as i64 - b.0 as i64).unsigned_abs();
+    let dy = (a.1 as i64 - b.1 as i64).unsigned_abs();
+
+    if dx < dy {
+        (dx, dy - dx)
+    } else {
+        (dy, dx - dy)
+    }
+}
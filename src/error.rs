@@ -0,0 +1,98 @@
+use std::error::Error;
+use std::fmt;
+
+use movingai::Coords2D;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+///Reasons a search can fail to return a Route.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PathError {
+    ///There is no route between the start and goal.
+    NoPath,
+    ///A cost was NaN, infinite or negative.
+    InvalidCost,
+    ///JPS reached a tile which does not cost one or can not be entered from every direction, see
+    ///[`Grid::is_uniform`](crate::Grid::is_uniform).
+    NonUniformGrid,
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathError::NoPath => write!(f, "no route exists between the start and goal"),
+            PathError::InvalidCost => write!(f, "cost was NaN, infinite or negative"),
+            PathError::NonUniformGrid => write!(f, "grid does not have uniform costs and moves"),
+        }
+    }
+}
+
+impl Error for PathError {}
+
+///Reasons a Route can fail validation against a map.
+///Step indices count from the goal, in the same order as [`Route::steps`](crate::Route::steps).
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RouteError {
+    ///A step is on a tile which can not be moved through.
+    Impassable { index: usize, step: Coords2D },
+    ///Two consecutive steps can not be moved between under the rules' neighbourhood.
+    IllegalMove {
+        index: usize,
+        from: Coords2D,
+        to: Coords2D,
+    },
+    ///The route does not start where expected.
+    WrongStart {
+        expected: Coords2D,
+        found: Option<Coords2D>,
+    },
+    ///The route does not end where expected.
+    WrongGoal {
+        expected: Coords2D,
+        found: Option<Coords2D>,
+    },
+    ///The stored distance does not match the distance found by measuring the route.
+    WrongDistance { expected: f64, found: f64 },
+    ///A tile cost was NaN, infinite or negative.
+    InvalidCost { index: usize, step: Coords2D },
+    ///The speed of a tile was zero, negative, NaN or infinite.
+    InvalidSpeed { index: usize, step: Coords2D },
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RouteError::Impassable { index, step } => {
+                write!(f, "step {} at {:?} is impassable", index, step)
+            }
+            RouteError::IllegalMove { index, from, to } => {
+                write!(f, "step {} can not move from {:?} to {:?}", index, from, to)
+            }
+            RouteError::WrongStart { expected, found } => {
+                write!(f, "route starts at {:?} rather than {:?}", found, expected)
+            }
+            RouteError::WrongGoal { expected, found } => {
+                write!(f, "route ends at {:?} rather than {:?}", found, expected)
+            }
+            RouteError::WrongDistance { expected, found } => write!(
+                f,
+                "route has a distance of {} rather than {}",
+                found, expected
+            ),
+            RouteError::InvalidCost { index, step } => write!(
+                f,
+                "step {} at {:?} has a cost which is NaN, infinite or negative",
+                index, step
+            ),
+            RouteError::InvalidSpeed { index, step } => write!(
+                f,
+                "step {} at {:?} has a speed which is not positive and finite",
+                index, step
+            ),
+        }
+    }
+}
+
+impl Error for RouteError {}
@@ -0,0 +1,162 @@
+use movingai::Coords2D;
+
+use crate::utils::centre;
+use crate::{Grid, Point, Route};
+
+///What an agent following a route should do next, returned by [`PathFollower::update`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FollowStatus {
+    ///The agent is on the route and should move towards the target step.
+    Following { target: Coords2D },
+    ///The agent has reached the goal.
+    Arrived,
+    ///The agent is further from the route than the follower's tolerance, so a new route is needed.
+    OffPath { deviation: f64 },
+    ///The next step can no longer be moved through, so a new route is needed.
+    Blocked { step: Coords2D },
+}
+
+impl FollowStatus {
+    ///Returns whether a new route should be found.
+    pub fn needs_replan(&self) -> bool {
+        matches!(
+            self,
+            FollowStatus::OffPath { .. } | FollowStatus::Blocked { .. }
+        )
+    }
+}
+
+///Tracks an agent's progress along a route.
+///
+///The follower is updated with the agent's position as it moves. It advances past each step once the
+///agent is within the follower's tolerance of it or has moved beyond it along the route, and reports
+///when the agent has drifted off the route or the next step has become blocked on the map.
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use std::path::Path;
+/// use blitz_path::{FollowStatus, PathFollower};
+///
+/// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+/// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+/// let scene = &scenes[0];
+///
+/// if let Some(path) = blitz_path::a_star_path(&map, scene.start_pos, scene.goal_pos) {
+///     let mut follower = PathFollower::new(path, 0.5);
+///     let mut position = (scene.start_pos.0 as f64, scene.start_pos.1 as f64);
+///
+///     //Move straight to each target in turn
+///     while let FollowStatus::Following { target } = follower.update(&map, position) {
+///         position = (target.0 as f64, target.1 as f64);
+///     }
+///
+///     assert_eq!(follower.update(&map, position), FollowStatus::Arrived);
+/// }
+/// # Ok::<(), io::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathFollower<C = f64> {
+    route: Route<C>,
+    //Number of steps reached, counting from the start
+    index: usize,
+    tolerance: f64,
+}
+
+impl<C> PathFollower<C> {
+    ///Creates a follower starting at the start of a route. The agent may be up to `tolerance` tiles
+    ///from the route before it is considered off the path, and reaches a step once it is that close.
+    pub fn new(route: Route<C>, tolerance: f64) -> PathFollower<C> {
+        PathFollower {
+            route,
+            index: 1,
+            tolerance,
+        }
+    }
+
+    ///Returns the route being followed.
+    pub fn route(&self) -> &Route<C> {
+        &self.route
+    }
+
+    ///Returns the route being followed, consuming the follower.
+    pub fn into_route(self) -> Route<C> {
+        self.route
+    }
+
+    ///Returns the number of steps which have been reached, counting from the start.
+    pub fn index(&self) -> usize {
+        self.index.min(self.route.len())
+    }
+
+    ///Returns the step the agent is moving towards, or None if it has arrived.
+    pub fn target(&self) -> Option<Coords2D> {
+        self.remaining().last().copied()
+    }
+
+    ///Returns the steps which have not been reached yet, in the same goal first order as
+    ///[`Route::as_slice`].
+    pub fn remaining(&self) -> &[Coords2D] {
+        let steps = self.route.as_slice();
+        &steps[..steps.len().saturating_sub(self.index)]
+    }
+
+    ///Updates the follower with the agent's current position, advancing past every step the agent has
+    ///moved beyond, and returns what the agent should do next.
+    pub fn update<G: Grid>(&mut self, map: &G, position: Point) -> FollowStatus {
+        let steps = self.route.as_slice();
+        let len = steps.len();
+
+        loop {
+            if self.index >= len {
+                return FollowStatus::Arrived;
+            }
+
+            //Steps are stored goal first
+            let (from, target) = (steps[len - self.index], steps[len - 1 - self.index]);
+            let (along, deviation) = project(position, centre(from), centre(target));
+
+            if deviation > self.tolerance {
+                //The agent may have moved past the target onto a later part of the route
+                let later = (self.index + 1..len).find(|&index| {
+                    let (from, to) = (steps[len - index], steps[len - 1 - index]);
+                    project(position, centre(from), centre(to)).1 <= self.tolerance
+                });
+                match later {
+                    Some(index) => {
+                        self.index = index;
+                        continue;
+                    }
+                    None => return FollowStatus::OffPath { deviation },
+                }
+            }
+            if !map.is_passable(target) {
+                return FollowStatus::Blocked { step: target };
+            }
+
+            let (dx, dy) = (position.0 - target.0 as f64, position.1 - target.1 as f64);
+            if along < 1.0 && (dx * dx + dy * dy).sqrt() > self.tolerance {
+                return FollowStatus::Following { target };
+            }
+
+            self.index += 1;
+        }
+    }
+}
+
+//How far along a segment a point is, as a fraction of its length, and its distance from the segment
+fn project(point: Point, a: Point, b: Point) -> (f64, f64) {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    let along = if length > 0.0 {
+        ((point.0 - a.0) * dx + (point.1 - a.1) * dy) / length
+    } else {
+        1.0
+    };
+
+    let fraction = along.clamp(0.0, 1.0);
+    let closest = (a.0 + dx * fraction, a.1 + dy * fraction);
+    let deviation = ((point.0 - closest.0).powi(2) + (point.1 - closest.1).powi(2)).sqrt();
+
+    (along, deviation)
+}
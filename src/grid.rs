@@ -0,0 +1,146 @@
+use movingai::{Coords2D, Map2D, MovingAiMap};
+
+///A two dimensional grid of tiles which can be searched for routes.
+///
+///Only the dimensions and `is_passable` need to be implemented. Grids move in eight directions,
+///but may only move diagonally when both of the straight steps making up the diagonal are passable.
+///Grids with further rules about which moves are allowed, such as MovingAI maps where some terrain
+///can only be entered from similar terrain, implement `can_move`.
+pub trait Grid {
+    ///Returns the width of the grid in tiles.
+    fn width(&self) -> usize;
+
+    ///Returns the height of the grid in tiles.
+    fn height(&self) -> usize;
+
+    ///Whether the tile can be moved through. Must return false for tiles outside the grid.
+    fn is_passable(&self, tile: Coords2D) -> bool;
+
+    ///Returns the multiplier applied to the cost of moving into a tile, which defaults to one.
+    ///Costs must be finite and not negative, otherwise searches return a PathError.
+    ///Costs below one can cause A* to return a route which is not the shortest.
+    ///JPS only works on tiles with uniform costs, see `is_uniform`.
+    fn cost(&self, _tile: Coords2D) -> f64 {
+        1.0
+    }
+
+    ///Whether a single step from a passable tile to a neighbouring passable tile is allowed,
+    ///which defaults to true. Diagonal steps must also not cut corners, whatever this returns.
+    fn can_move(&self, _from: Coords2D, _to: Coords2D) -> bool {
+        true
+    }
+
+    ///Whether a passable tile costs one and can be entered from every passable neighbour.
+    ///JPS skips over tiles, so only works on tiles like this and returns
+    ///[`PathError::NonUniformGrid`](crate::PathError::NonUniformGrid) when it reaches any other.
+    ///The default implementation checks `cost`, so grids which implement `can_move` should also
+    ///implement this.
+    fn is_uniform(&self, tile: Coords2D) -> bool {
+        self.cost(tile) == 1.0
+    }
+
+    ///Returns the first tile, moving from `tile` in a straight `direction`, which is impassable or
+    ///has a forced neighbour, i.e. a blocked tile to the side with a passable tile after it.
+    ///JPS uses this to skip over tiles which cannot be jump points. The returned tile may be `tile`
+    ///itself and must never be past the first such tile. Tiles which are not
+    ///[uniform](Grid::is_uniform) need not stop the scan, as JPS checks each tile skipped. The default
+    ///implementation returns `tile`, leaving JPS to check each tile in turn.
+    fn scan(&self, tile: Coords2D, _direction: (i32, i32)) -> Coords2D {
+        tile
+    }
+
+    ///Returns an iterator over each passable tile which can be reached in a single step from `tile`.
+    fn neighbours(&self, tile: Coords2D) -> Neighbours<'_, Self>
+    where
+        Self: Sized,
+    {
+        Neighbours {
+            grid: self,
+            tile,
+            index: 0,
+        }
+    }
+}
+
+impl Grid for MovingAiMap {
+    #[inline]
+    fn width(&self) -> usize {
+        Map2D::width(self)
+    }
+
+    #[inline]
+    fn height(&self) -> usize {
+        Map2D::height(self)
+    }
+
+    #[inline]
+    fn is_passable(&self, tile: Coords2D) -> bool {
+        self.is_traversable(tile)
+    }
+
+    //Swamp and water can only be entered from similar terrain, and maps which are not octile only
+    //move in four directions
+    #[inline]
+    fn can_move(&self, from: Coords2D, to: Coords2D) -> bool {
+        self.is_traversable_from(from, to)
+    }
+
+    #[inline]
+    fn is_uniform(&self, tile: Coords2D) -> bool {
+        matches!(self.get(tile), '.' | 'G')
+    }
+}
+
+pub(crate) const DIRECTIONS: [(i32, i32); 8] = [
+    (1, 0),
+    (1, 1),
+    (1, -1),
+    (0, 1),
+    (0, -1),
+    (-1, 0),
+    (-1, -1),
+    (-1, 1),
+];
+
+///Iterator over the neighbours of a tile, created by [`Grid::neighbours`].
+pub struct Neighbours<'a, G> {
+    grid: &'a G,
+    tile: Coords2D,
+    index: usize,
+}
+
+impl<'a, G: Grid> Iterator for Neighbours<'a, G> {
+    type Item = Coords2D;
+
+    fn next(&mut self) -> Option<Coords2D> {
+        let (x, y) = self.tile;
+
+        while let Some(&(dx, dy)) = DIRECTIONS.get(self.index) {
+            self.index += 1;
+
+            let next = (
+                (x as i64 + dx as i64) as usize,
+                (y as i64 + dy as i64) as usize,
+            );
+            if !self.grid.is_passable(next) {
+                continue;
+            }
+
+            //Diagonal steps must not cut corners
+            if dx != 0
+                && dy != 0
+                && !(self.grid.is_passable((next.0, y)) && self.grid.is_passable((x, next.1)))
+            {
+                continue;
+            }
+
+            if !self.grid.can_move(self.tile, next) {
+                continue;
+            }
+
+            return Some(next);
+        }
+
+        None
+    }
+}
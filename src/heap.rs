@@ -0,0 +1,96 @@
+use std::cmp::Ordering;
+
+///A binary max-heap whose items are compared by a function given to each call, so the ordering can
+///depend on state held outside the items.
+///
+///The same function must be given to every call for the heap to stay ordered.
+#[derive(Debug)]
+pub struct Heap<T> {
+    items: Vec<T>,
+}
+
+impl<T> Heap<T> {
+    pub fn new() -> Heap<T> {
+        Heap { items: Vec::new() }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Heap<T> {
+        Heap {
+            items: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn push<F: Fn(&T, &T) -> Ordering>(&mut self, item: T, cmp: &F) {
+        self.items.push(item);
+
+        //Sift the new item up past any smaller parents
+        let mut index = self.items.len() - 1;
+        while index > 0 {
+            let parent = (index - 1) / 2;
+            if cmp(&self.items[index], &self.items[parent]) != Ordering::Greater {
+                break;
+            }
+            self.items.swap(index, parent);
+            index = parent;
+        }
+    }
+
+    pub fn pop<F: Fn(&T, &T) -> Ordering>(&mut self, cmp: &F) -> Option<T> {
+        if self.items.is_empty() {
+            return None;
+        }
+        let item = self.items.swap_remove(0);
+
+        //Sift the moved item down past any greater children
+        let len = self.items.len();
+        let mut index = 0;
+        loop {
+            let left = 2 * index + 1;
+            let right = left + 1;
+            let mut largest = index;
+
+            if left < len && cmp(&self.items[left], &self.items[largest]) == Ordering::Greater {
+                largest = left;
+            }
+            if right < len && cmp(&self.items[right], &self.items[largest]) == Ordering::Greater {
+                largest = right;
+            }
+            if largest == index {
+                break;
+            }
+            self.items.swap(index, largest);
+            index = largest;
+        }
+
+        Some(item)
+    }
+
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.items.first()
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.items
+    }
+}
+
+impl<T> Default for Heap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
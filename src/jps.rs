@@ -1,11 +1,14 @@
-use std::collections::BinaryHeap;
+use std::time::Instant;
 
 use movingai::Coords2D;
-use movingai::Map2D;
 
+use crate::cost::{Cost, Fixed};
+use crate::grid::DIRECTIONS;
 use crate::node::Node;
-use crate::utils::{direction, distance, rewind_jps};
-use crate::Route;
+use crate::observer::SearchObserver;
+use crate::open::OpenList;
+use crate::utils::{direction, rewind_jps};
+use crate::{Grid, PathError, Route, SearchOptions, SearchStats};
 
 #[derive(Copy, Clone)]
 enum Direction {
@@ -35,38 +38,215 @@ enum Direction {
 /// }
 /// # Ok::<(), io::Error>(())
 /// ```
+#[inline]
+pub fn jps_path<G: Grid>(map: &G, start: Coords2D, goal: Coords2D) -> Option<Route> {
+    jps_path_with(map, start, goal, &SearchOptions::default()).ok()
+}
 
+///Creates a new route using the JPS algorithm with fixed-point costs.
+///Costs are measured in octile steps using integer arithmetic and nodes are queued in a radix heap,
+///which avoids floating-point comparisons and gives the same route on every platform.
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use std::path::Path;
+/// use blitz_path::Cost;
+///
+/// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+/// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+/// let scene = &scenes[0];
+///
+/// let path = blitz_path::jps_path_fixed(&map, scene.start_pos, scene.goal_pos);
+///
+/// if let Some(path) = path {
+///     assert!((scene.optimal_length - path.distance().to_f64()).abs() < 1e-4);
+/// }
+/// # Ok::<(), io::Error>(())
+/// ```
 #[inline]
-pub fn jps_path<U, T: Map2D<U>>(map: &T, start: Coords2D, goal: Coords2D) -> Option<Route> {
+pub fn jps_path_fixed<G: Grid>(map: &G, start: Coords2D, goal: Coords2D) -> Option<Route<Fixed>> {
+    jps_path_with(map, start, goal, &SearchOptions::default()).ok()
+}
+
+///Creates a new route using the JPS algorithm, configured by the given SearchOptions.
+///The distance of the route is measured using the Cost type `C`, which is usually inferred from the
+///type of Route expected.
+///Returns a PathError if there is no route to the goal or a cost used in the search is invalid.
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use std::path::Path;
+/// use blitz_path::{Route, SearchOptions, TieBreak};
+///
+/// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+/// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+/// let scene = &scenes[0];
+///
+/// let mut options = SearchOptions::default();
+/// options.tie_break = TieBreak::LowerH;
+/// let path: Route<f32> = blitz_path::jps_path_with(&map, scene.start_pos, scene.goal_pos, &options)?;
+///
+/// assert_eq!(scene.optimal_length as f32, path.distance());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[inline]
+pub fn jps_path_with<C: Cost, G: Grid>(
+    map: &G,
+    start: Coords2D,
+    goal: Coords2D,
+    options: &SearchOptions,
+) -> Result<Route<C>, PathError> {
+    search(
+        map,
+        start,
+        goal,
+        options,
+        &mut SearchStats::default(),
+        &mut (),
+    )
+}
+
+///Creates a new route using the JPS algorithm in the same way as [`jps_path_with`], also returning
+///measures of the work done by the search, whether or not it found a route.
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use std::path::Path;
+/// use blitz_path::{Route, SearchOptions};
+///
+/// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+/// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+/// let scene = &scenes[0];
+///
+/// let options = SearchOptions::default();
+/// let (path, stats) = blitz_path::jps_path_with_stats(&map, scene.start_pos, scene.goal_pos, &options);
+/// let path: Route = path?;
+///
+/// assert!(stats.jump_points > 0);
+/// assert_eq!(stats.reopened, 0);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn jps_path_with_stats<C: Cost, G: Grid>(
+    map: &G,
+    start: Coords2D,
+    goal: Coords2D,
+    options: &SearchOptions,
+) -> (Result<Route<C>, PathError>, SearchStats) {
+    let mut stats = SearchStats::default();
+    let timer = Instant::now();
+    let result = search(map, start, goal, options, &mut stats, &mut ());
+    stats.elapsed = timer.elapsed();
+
+    (result, stats)
+}
+
+///Creates a new route using the JPS algorithm in the same way as [`jps_path_with`], reporting each
+///step of the search to an observer. Only jump points are pushed to the open list, so the observer
+///sees far fewer nodes than it would from A*.
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use std::path::Path;
+/// use blitz_path::{Coords2D, Route, SearchObserver, SearchOptions};
+///
+/// //Records every jump point found
+/// struct JumpPoints(Vec<Coords2D>);
+///
+/// impl SearchObserver for JumpPoints {
+///     fn pushed(&mut self, node: Coords2D, _parent: Coords2D, _g: f64, _h: f64) {
+///         self.0.push(node);
+///     }
+/// }
+///
+/// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+/// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+/// let scene = &scenes[0];
+///
+/// let mut jump_points = JumpPoints(Vec::new());
+/// let options = SearchOptions::default();
+/// let path: Route = blitz_path::jps_path_observed(&map, scene.start_pos, scene.goal_pos, &options, &mut jump_points)?;
+///
+/// assert!(jump_points.0.contains(&scene.goal_pos));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[cfg(feature = "observer")]
+pub fn jps_path_observed<C: Cost, G: Grid, O: SearchObserver>(
+    map: &G,
+    start: Coords2D,
+    goal: Coords2D,
+    options: &SearchOptions,
+    observer: &mut O,
+) -> Result<Route<C>, PathError> {
+    search(
+        map,
+        start,
+        goal,
+        options,
+        &mut SearchStats::default(),
+        observer,
+    )
+}
+
+#[inline]
+fn search<C: Cost, G: Grid, O: SearchObserver>(
+    map: &G,
+    start: Coords2D,
+    goal: Coords2D,
+    options: &SearchOptions,
+    stats: &mut SearchStats,
+    observer: &mut O,
+) -> Result<Route<C>, PathError> {
     if start == goal {
-        return Some(Route::from((0.0, vec![])));
+        return Ok(Route::from((C::zero(), vec![])));
     }
 
     //Push start node to open list
-    let start_node = Node::new(0.0, distance(start, goal), start, start);
-
-    //Add start's neighbours to open list, needs to add all surrounds as JPS relies on cutting corners
-    //and 8 directions of movement.
-    let prev_x = start_node.position.0 - 1;
-    let next_x = start_node.position.0 + 1;
-    let prev_y = start_node.position.1 - 1;
-    let next_y = start_node.position.1 + 1;
+    let start_node = Node::new(C::zero(), C::distance(start, goal), start, start);
+    if !start_node.is_valid() {
+        return Err(PathError::InvalidCost);
+    }
 
     //Initialize open and closed lists
-    let capacity = (1 + next_x - prev_x) * (1 + next_y - prev_y);
-    let mut open = BinaryHeap::with_capacity(capacity);
+    let capacity = DIRECTIONS.len() + 1;
+    let mut open = OpenList::with_capacity(capacity, options.tie_break);
     let mut closed = Vec::with_capacity(capacity);
 
-    for x in prev_x..=next_x {
-        for y in prev_y..=next_y {
-            open.push(Node::from_parent(&start_node, (x, y), goal));
+    //Add start's neighbours to open list, needs to add all surrounds as JPS relies on cutting corners
+    //and 8 directions of movement. Grids have no border, so neighbours off the grid are skipped.
+    observer.pushed(start, start, 0.0, start_node.h.to_f64());
+    observer.expanded(start);
+    for &(dx, dy) in DIRECTIONS.iter() {
+        let (x, y) = (start.0 as i64 + dx as i64, start.1 as i64 + dy as i64);
+        if x < 0 || y < 0 || x as usize >= map.width() || y as usize >= map.height() {
+            continue;
+        }
+
+        let node = Node::from_parent(&start_node, (x as usize, y as usize), goal);
+        if !node.is_valid() {
+            return Err(PathError::InvalidCost);
         }
+
+        open.push(node);
+        observer.pushed(node.position, start, node.g.to_f64(), node.h.to_f64());
     }
 
     closed.push(start_node);
+    observer.closed(start);
+    stats.expanded = 1;
+    stats.generated = 1 + open.len();
+    stats.max_open = open.len();
 
     //Examine the nodes
     while let Some(node_current) = open.pop() {
+        //Jumping is only correct across tiles which cost one and can be entered from any direction
+        if map.is_passable(node_current.position) && !map.is_uniform(node_current.position) {
+            return Err(PathError::NonUniformGrid);
+        }
+
         //If this is the target node return the distance to get there
         if node_current.position == goal {
             //Push all remaining to closed
@@ -74,8 +254,9 @@ pub fn jps_path<U, T: Map2D<U>>(map: &T, start: Coords2D, goal: Coords2D) -> Opt
 
             //Unwind
             let path = rewind_jps(&node_current, &closed);
+            observer.found(&path, node_current.g.to_f64());
             let route = Route::from((node_current.g, path));
-            return Some(route);
+            return Ok(route);
         }
 
         //Check if node is on closed list and continue if is
@@ -83,47 +264,60 @@ pub fn jps_path<U, T: Map2D<U>>(map: &T, start: Coords2D, goal: Coords2D) -> Opt
             continue;
         }
 
+        stats.expanded += 1;
+        observer.expanded(node_current.position);
+
         //Calculate direction
         let direction = direction(node_current.position, node_current.parent);
 
-        if let Some(nodes) = check_jump(&node_current, map, direction, goal) {
+        if let Some(nodes) = check_jump(&node_current, map, direction, goal)? {
             for node in nodes {
+                if !node.is_valid() {
+                    return Err(PathError::InvalidCost);
+                }
+
+                observer.jumped(node.position);
                 open.push(node);
+                observer.pushed(node.position, node.parent, node.g.to_f64(), node.h.to_f64());
+                stats.generated += 1;
+                stats.jump_points += 1;
             }
+            stats.max_open = stats.max_open.max(open.len());
         }
 
         //Push current node to closed list
         closed.push(node_current);
+        observer.closed(node_current.position);
     }
 
-    None
+    Err(PathError::NoPath)
 }
 
 #[inline]
-fn check_jump<U, T: Map2D<U>>(
-    parent: &Node,
-    map: &T,
+fn check_jump<C: Cost, G: Grid>(
+    parent: &Node<Coords2D, C>,
+    map: &G,
     (dx, dy): (i32, i32),
     goal: Coords2D,
-) -> Option<Vec<Node>> {
+) -> Result<Option<Vec<Node<Coords2D, C>>>, PathError> {
     if dx != 0 {
         if dy != 0 {
-            expand(map, &parent, Direction::Diagonal(dx, dy), goal)
+            expand(map, parent, Direction::Diagonal(dx, dy), goal)
         } else {
-            expand(map, &parent, Direction::Horizontal(dx), goal)
+            expand(map, parent, Direction::Horizontal(dx), goal)
         }
     } else if dy != 0 {
-        expand(map, &parent, Direction::Vertical(dy), goal)
+        expand(map, parent, Direction::Vertical(dy), goal)
     } else {
-        None
+        Ok(None)
     }
 }
 
 #[inline]
-fn forced_horizontal<U, T: Map2D<U>>(
-    nodes: &mut Vec<Node>,
-    map: &T,
-    check_node: &Node,
+fn forced_horizontal<C: Cost, G: Grid>(
+    nodes: &mut Vec<Node<Coords2D, C>>,
+    map: &G,
+    check_node: &Node<Coords2D, C>,
     direction: i32,
     goal: Coords2D,
 ) {
@@ -133,21 +327,21 @@ fn forced_horizontal<U, T: Map2D<U>>(
     let down_y = (check_y as i32 + 1) as usize;
 
     //Check if blocked up
-    if !map.is_traversable((check_x, up_y)) && map.is_traversable((next_x, up_y)) {
-        nodes.push(Node::from_parent(&check_node, (next_x, up_y), goal));
+    if !map.is_passable((check_x, up_y)) && map.is_passable((next_x, up_y)) {
+        nodes.push(Node::from_parent(check_node, (next_x, up_y), goal));
     }
 
     //Check if blocked down
-    if !map.is_traversable((check_x, down_y)) && map.is_traversable((next_x, down_y)) {
-        nodes.push(Node::from_parent(&check_node, (next_x, down_y), goal));
+    if !map.is_passable((check_x, down_y)) && map.is_passable((next_x, down_y)) {
+        nodes.push(Node::from_parent(check_node, (next_x, down_y), goal));
     }
 }
 
 #[inline]
-fn forced_vertical<U, T: Map2D<U>>(
-    nodes: &mut Vec<Node>,
-    map: &T,
-    check_node: &Node,
+fn forced_vertical<C: Cost, G: Grid>(
+    nodes: &mut Vec<Node<Coords2D, C>>,
+    map: &G,
+    check_node: &Node<Coords2D, C>,
     direction: i32,
     goal: Coords2D,
 ) {
@@ -157,36 +351,52 @@ fn forced_vertical<U, T: Map2D<U>>(
     let next_y = (check_y as i32 + direction) as usize;
 
     //Check if blocked left
-    if !map.is_traversable((left_x, check_y)) && map.is_traversable((left_x, next_y)) {
-        nodes.push(Node::from_parent(&check_node, (left_x, next_y), goal));
+    if !map.is_passable((left_x, check_y)) && map.is_passable((left_x, next_y)) {
+        nodes.push(Node::from_parent(check_node, (left_x, next_y), goal));
     }
 
     //Check if blocked right
-    if !map.is_traversable((right_x, check_y)) && map.is_traversable((right_x, next_y)) {
-        nodes.push(Node::from_parent(&check_node, (right_x, next_y), goal));
+    if !map.is_passable((right_x, check_y)) && map.is_passable((right_x, next_y)) {
+        nodes.push(Node::from_parent(check_node, (right_x, next_y), goal));
     }
 }
 
 #[inline]
-fn expand<U, T: Map2D<U>>(
-    map: &T,
-    start_node: &Node,
+fn expand<C: Cost, G: Grid>(
+    map: &G,
+    start_node: &Node<Coords2D, C>,
     direction: Direction,
     goal: Coords2D,
-) -> Option<Vec<Node>> {
+) -> Result<Option<Vec<Node<Coords2D, C>>>, PathError> {
     let mut current = *start_node;
     let mut nodes = Vec::new();
     loop {
+        //Skip ahead to the next tile which could be a jump point
+        let straight = match direction {
+            Direction::Vertical(vert) => Some((0, vert)),
+            Direction::Horizontal(hor) => Some((hor, 0)),
+            Direction::Diagonal(_, _) => None,
+        };
+        if let Some(dir) = straight {
+            let position = scan(map, current.position, dir, goal);
+            if position != current.position {
+                current = Node::from_parent(start_node, position, goal);
+            }
+        }
+
         //Check if goal
         if current.position == goal {
             nodes.push(current);
 
-            return Some(nodes);
+            return Ok(Some(nodes));
         }
 
         //Check blocked
-        if !map.is_traversable(current.position) {
-            return None;
+        if !map.is_passable(current.position) {
+            return Ok(None);
+        }
+        if !map.is_uniform(current.position) {
+            return Err(PathError::NonUniformGrid);
         }
 
         //Otherwise Expand depending on direction
@@ -205,12 +415,14 @@ fn expand<U, T: Map2D<U>>(
             }
             Direction::Diagonal(hor, vert) => {
                 //Expand horizontally
-                if let Some(mut hor_nodes) = expand(map, &current, Direction::Horizontal(hor), goal)
+                if let Some(mut hor_nodes) =
+                    expand(map, &current, Direction::Horizontal(hor), goal)?
                 {
                     nodes.append(&mut hor_nodes);
                 }
                 //Expand vertically
-                if let Some(mut vert_nodes) = expand(map, &current, Direction::Vertical(vert), goal)
+                if let Some(mut vert_nodes) =
+                    expand(map, &current, Direction::Vertical(vert), goal)?
                 {
                     nodes.append(&mut vert_nodes);
                 }
@@ -229,10 +441,48 @@ fn expand<U, T: Map2D<U>>(
             nodes.push(current);
             nodes.push(next_node);
 
-            return Some(nodes);
+            return Ok(Some(nodes));
         }
 
         //Else move onto next tile
         current = Node::from_parent(start_node, next_position, goal);
     }
 }
+
+//Finds the next tile in a straight line which is blocked, has forced neighbours, is not uniform or is
+//the goal
+#[inline]
+fn scan<G: Grid>(map: &G, position: Coords2D, direction: (i32, i32), goal: Coords2D) -> Coords2D {
+    let mut stop = map.scan(position, direction);
+
+    //The grid's scan only looks for blocked tiles and forced neighbours, so stop at any tile it skipped
+    //which JPS can't jump over, leaving expand to report it
+    let mut tile = position;
+    while tile != stop {
+        tile = (
+            (tile.0 as isize + direction.0 as isize) as usize,
+            (tile.1 as isize + direction.1 as isize) as usize,
+        );
+        if map.is_passable(tile) && !map.is_uniform(tile) {
+            stop = tile;
+        }
+    }
+
+    let (start, end, target, on_line) = if direction.0 != 0 {
+        (position.0, stop.0, goal.0, goal.1 == position.1)
+    } else {
+        (position.1, stop.1, goal.1, goal.0 == position.0)
+    };
+    let step = (direction.0 + direction.1) as isize;
+    let (start, end, target) = (
+        start as isize * step,
+        end as isize * step,
+        target as isize * step,
+    );
+
+    if on_line && start <= target && target < end {
+        goal
+    } else {
+        stop
+    }
+}
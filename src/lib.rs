@@ -2,7 +2,11 @@
 //!
 //! `blitz-path` contains (hopefully) lightning-quick implementations of various pathfinding algorithms. Currently in a very wip state. It relies on the [movingai-rust](https://github.com/THeK3nger/movingai-rust) crate for map implementation and testing / benchmarks.
 //!
-//! It currently provides implementations of the A* and JPS pathfinding algorithms.
+//! Searches run on any type implementing the [`Grid`] trait, which is implemented for `movingai::MovingAiMap`.
+//!
+//! It currently provides implementations of the A* and JPS pathfinding algorithms. A* can also search any other graph, given a function returning the successors of each node, and the `petgraph` feature adds searches over [petgraph](https://docs.rs/petgraph) graphs. Searches can measure distances using any [`Cost`] type, including `f32` and [`Fixed`] point costs, which use integer arithmetic and give identical results on every platform.
+//!
+//! Open areas can also be described by a [`NavMesh`] of convex polygons, giving smooth routes with floating-point waypoints.
 //!
 //! *A note on compiling:* Compiling the crate with "fat" LTO can greatly improve performance. However, it also substantially slows down compilation, so it is only recommended to use this when building for release. To enable fat LTO for the `--release` flag add the following to your project's `cargo.toml` file.
 //! ```ignore
@@ -11,11 +15,56 @@
 //! ```
 
 mod astar;
+mod batch;
+mod bitgrid;
+mod compact;
+mod cost;
+mod error;
+mod follower;
+mod grid;
+mod heap;
 mod jps;
+mod navmesh;
 mod node;
+mod observer;
+mod open;
+mod options;
+#[cfg(feature = "petgraph")]
+pub mod petgraph;
+mod radix;
+mod render;
 mod route;
+mod schedule;
+mod sight;
+mod spline;
+mod stats;
 mod utils;
+mod visibility;
 
-pub use astar::a_star_path;
-pub use jps::jps_path;
+#[cfg(feature = "observer")]
+pub use astar::a_star_path_observed;
+pub use astar::SearchContext;
+pub use astar::{a_star, a_star_path, a_star_path_fixed, a_star_path_with, a_star_path_with_stats};
+pub use batch::{find_paths, find_paths_with};
+pub use bitgrid::BitGrid;
+pub use compact::{CompactRoute, Segment};
+pub use cost::{Cost, Fixed};
+pub use error::{PathError, RouteError};
+pub use follower::{FollowStatus, PathFollower};
+pub use grid::{Grid, Neighbours};
+#[cfg(feature = "observer")]
+pub use jps::jps_path_observed;
+pub use jps::{jps_path, jps_path_fixed, jps_path_with, jps_path_with_stats};
+pub use movingai::Coords2D;
+pub use navmesh::{navmesh_path, navmesh_path_with, NavMesh, Point};
+#[cfg(feature = "observer")]
+pub use observer::SearchObserver;
+pub use options::{Neighbourhood, SearchOptions, TieBreak, ValidationRules};
+pub use render::{render_ascii, render_ppm, Overlay};
 pub use route::Route;
+pub use schedule::Schedule;
+pub use sight::{line_of_sight, raycast};
+pub use spline::Spline;
+pub use stats::SearchStats;
+pub use utils::distance;
+pub use visibility::{visibility_path, visibility_path_with, VisibilityGraph};
@@ -0,0 +1,409 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+
+use crate::utils::length;
+use crate::{Grid, PathError, Route, SearchOptions};
+
+///A point in continuous space, measured in the same units as tiles, i.e. the centre of tile (x, y) is
+///at the point (x, y).
+pub type Point = (f64, f64);
+
+///A navigation mesh made of convex polygons which can be moved through freely.
+///
+///Polygons are connected wherever they share an edge, i.e. two neighbouring vertices of one polygon are
+///also neighbouring vertices of the other. Polygons which only touch at part of an edge are not
+///connected, so the vertices of one must be added to the other's edge.
+///
+/// # Examples
+///
+/// ```
+/// use blitz_path::NavMesh;
+///
+/// //An L-shaped room made of two squares
+/// let vertices = vec![(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0), (4.0, 0.0), (4.0, 2.0), (2.0, 4.0), (4.0, 4.0)];
+/// let mesh = NavMesh::new(vertices, vec![vec![0, 1, 2, 3], vec![1, 4, 5, 2], vec![2, 5, 7, 6]]);
+///
+/// let route = blitz_path::navmesh_path(&mesh, (0.5, 1.5), (3.0, 3.5)).unwrap();
+///
+/// assert_eq!(route.steps(), vec![(3.0, 3.5), (2.0, 2.0), (0.5, 1.5)]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "NavMeshData"))]
+pub struct NavMesh {
+    vertices: Vec<Point>,
+    polygons: Vec<Vec<usize>>,
+    centres: Vec<Point>,
+    //Neighbouring polygons and the vertices of the shared edge, in the order they appear around this polygon
+    portals: Vec<Vec<(usize, usize, usize)>>,
+}
+
+//The vertices and polygons of a NavMesh as loaded. The rest of the mesh is rebuilt from them, so
+//only these need to be checked.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct NavMeshData {
+    vertices: Vec<Point>,
+    polygons: Vec<Vec<usize>>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<NavMeshData> for NavMesh {
+    type Error = String;
+
+    fn try_from(data: NavMeshData) -> Result<Self, Self::Error> {
+        if !data
+            .vertices
+            .iter()
+            .all(|vertex| vertex.0.is_finite() && vertex.1.is_finite())
+        {
+            return Err(String::from("navmesh vertex is not finite"));
+        }
+
+        for (index, polygon) in data.polygons.iter().enumerate() {
+            if polygon.len() < 3 {
+                return Err(format!("polygon {} has fewer than three vertices", index));
+            }
+            if !polygon.iter().all(|vertex| *vertex < data.vertices.len()) {
+                return Err(format!(
+                    "polygon {} uses a vertex which does not exist",
+                    index
+                ));
+            }
+        }
+
+        Ok(NavMesh::new(data.vertices, data.polygons))
+    }
+}
+
+impl NavMesh {
+    ///Creates a mesh from a list of vertices and polygons, each given by the indices of its vertices in
+    ///order around it. Polygons must be convex, but may be listed clockwise or anticlockwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a polygon has fewer than three vertices or uses a vertex which does not exist.
+    pub fn new(vertices: Vec<Point>, mut polygons: Vec<Vec<usize>>) -> NavMesh {
+        let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut portals = vec![Vec::new(); polygons.len()];
+        let mut centres = Vec::with_capacity(polygons.len());
+
+        for (index, polygon) in polygons.iter_mut().enumerate() {
+            assert!(
+                polygon.len() >= 3,
+                "polygon {} has fewer than three vertices",
+                index
+            );
+            assert!(
+                polygon.iter().all(|vertex| *vertex < vertices.len()),
+                "polygon {} uses a vertex which does not exist",
+                index
+            );
+
+            //Store every polygon anticlockwise, so the inside is always to the left of its edges
+            let area: f64 = edges_of(polygon)
+                .map(|(a, b)| cross((0.0, 0.0), vertices[a], vertices[b]))
+                .sum();
+            if area < 0.0 {
+                polygon.reverse();
+            }
+
+            let count = polygon.len() as f64;
+            let sum = polygon.iter().fold((0.0, 0.0), |sum, vertex| {
+                (sum.0 + vertices[*vertex].0, sum.1 + vertices[*vertex].1)
+            });
+            centres.push((sum.0 / count, sum.1 / count));
+
+            //Connect polygons which share an edge
+            for (a, b) in edges_of(polygon) {
+                if let Some(other) = edges.remove(&(b, a)) {
+                    portals[index].push((other, a, b));
+                    portals[other].push((index, b, a));
+                } else {
+                    edges.insert((a, b), index);
+                }
+            }
+        }
+
+        NavMesh {
+            vertices,
+            polygons,
+            centres,
+            portals,
+        }
+    }
+
+    ///Creates a mesh covering the passable tiles of a grid, made by merging tiles into rectangles.
+    ///Each tile (x, y) covers the square from (x - 0.5, y - 0.5) to (x + 0.5, y + 0.5), so tile
+    ///coordinates can be used as points on the mesh. As on the grid, routes can not pass between two
+    ///tiles which only touch at a corner.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// use std::path::Path;
+    /// use blitz_path::NavMesh;
+    ///
+    /// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+    /// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+    /// let scene = &scenes[0];
+    ///
+    /// let mesh = NavMesh::from_grid(&map);
+    /// let start = (scene.start_pos.0 as f64, scene.start_pos.1 as f64);
+    /// let goal = (scene.goal_pos.0 as f64, scene.goal_pos.1 as f64);
+    ///
+    /// if let Some(route) = blitz_path::navmesh_path(&mesh, start, goal) {
+    ///     //Routes on a mesh can move at any angle, so are never longer than routes on the grid
+    ///     assert!(route.distance() <= scene.optimal_length + 1e-6);
+    /// }
+    /// # Ok::<(), io::Error>(())
+    /// ```
+    pub fn from_grid<G: Grid>(grid: &G) -> NavMesh {
+        let (width, height) = (grid.width(), grid.height());
+        let mut covered = vec![false; width * height];
+        let mut rectangles = Vec::new();
+
+        //Grow rectangles greedily, first along a row and then down as far as the whole row fits
+        for y in 0..height {
+            for x in 0..width {
+                if covered[y * width + x] || !grid.is_passable((x, y)) {
+                    continue;
+                }
+
+                let free = |x: usize, y: usize, covered: &[bool]| {
+                    !covered[y * width + x] && grid.is_passable((x, y))
+                };
+
+                let mut right = x + 1;
+                while right < width && free(right, y, &covered) {
+                    right += 1;
+                }
+
+                let mut bottom = y + 1;
+                while bottom < height && (x..right).all(|x| free(x, bottom, &covered)) {
+                    bottom += 1;
+                }
+
+                for row in y..bottom {
+                    covered[row * width + x..row * width + right].fill(true);
+                }
+                rectangles.push((x, y, right, bottom));
+            }
+        }
+
+        //Find every corner on each horizontal and vertical line, as a rectangle needs a vertex
+        //wherever the corner of a neighbouring rectangle touches one of its edges
+        let mut rows: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+        let mut columns: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+        for &(left, top, right, bottom) in rectangles.iter() {
+            for &(x, y) in [(left, top), (right, top), (right, bottom), (left, bottom)].iter() {
+                rows.entry(y).or_default().insert(x);
+                columns.entry(x).or_default().insert(y);
+            }
+        }
+
+        let mut indices = HashMap::new();
+        let mut vertices = Vec::new();
+        let mut polygons = Vec::with_capacity(rectangles.len());
+
+        for &(left, top, right, bottom) in rectangles.iter() {
+            //Walk around the rectangle, collecting the corners along each edge
+            let mut corners: Vec<(usize, usize)> = Vec::new();
+            corners.extend(rows[&top].range(left..right).map(|x| (*x, top)));
+            corners.extend(columns[&right].range(top..bottom).map(|y| (right, *y)));
+            corners.extend(
+                rows[&bottom]
+                    .range(left + 1..=right)
+                    .rev()
+                    .map(|x| (*x, bottom)),
+            );
+            corners.extend(
+                columns[&left]
+                    .range(top + 1..=bottom)
+                    .rev()
+                    .map(|y| (left, *y)),
+            );
+
+            let polygon = corners
+                .into_iter()
+                .map(|corner| {
+                    *indices.entry(corner).or_insert_with(|| {
+                        vertices.push((corner.0 as f64 - 0.5, corner.1 as f64 - 0.5));
+                        vertices.len() - 1
+                    })
+                })
+                .collect();
+            polygons.push(polygon);
+        }
+
+        NavMesh::new(vertices, polygons)
+    }
+
+    ///Returns the vertices of the mesh.
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    ///Returns the polygons of the mesh, each given by the indices of its vertices listed anticlockwise.
+    pub fn polygons(&self) -> &[Vec<usize>] {
+        &self.polygons
+    }
+
+    ///Returns the index of a polygon containing the point, if there is one.
+    pub fn locate(&self, point: Point) -> Option<usize> {
+        self.polygons.iter().position(|polygon| {
+            edges_of(polygon).all(|(a, b)| cross(self.vertices[a], self.vertices[b], point) >= 0.0)
+        })
+    }
+
+    //The point a search passes through in a polygon
+    fn waypoint(&self, polygon: usize, ends: [(usize, Point); 2]) -> Point {
+        match ends.iter().find(|end| end.0 == polygon) {
+            Some(end) => end.1,
+            None => self.centres[polygon],
+        }
+    }
+
+    //Builds the portals crossed by a route through the given polygons, as (left, right) pairs
+    fn funnel_portals(&self, polygons: &[usize], start: Point, goal: Point) -> Vec<(Point, Point)> {
+        let mut portals = Vec::with_capacity(polygons.len() + 1);
+        portals.push((start, start));
+
+        for pair in polygons.windows(2) {
+            if let Some(&(_, a, b)) = self.portals[pair[0]].iter().find(|x| x.0 == pair[1]) {
+                //Leaving an anticlockwise polygon, its edge runs from right to left
+                portals.push((self.vertices[b], self.vertices[a]));
+            }
+        }
+
+        portals.push((goal, goal));
+        portals
+    }
+}
+
+///Creates a new route across a navigation mesh.
+///A* finds the polygons to pass through, then the funnel algorithm pulls the route tight around the
+///corners of the mesh, giving the shortest route through those polygons.
+///Returns a Route struct containing the distance to the goal and each point where the route turns.
+///
+///See [`NavMesh`] for an example.
+pub fn navmesh_path(mesh: &NavMesh, start: Point, goal: Point) -> Option<Route<f64, Point>> {
+    navmesh_path_with(mesh, start, goal, &SearchOptions::default()).ok()
+}
+
+///Creates a new route across a navigation mesh, configured by the given SearchOptions.
+///Returns a PathError if there is no route to the goal, including if either point is outside the mesh.
+pub fn navmesh_path_with(
+    mesh: &NavMesh,
+    start: Point,
+    goal: Point,
+    options: &SearchOptions,
+) -> Result<Route<f64, Point>, PathError> {
+    if start == goal {
+        return Ok(Route::from((0.0, vec![])));
+    }
+
+    let start_polygon = mesh.locate(start).ok_or(PathError::NoPath)?;
+    let goal_polygon = mesh.locate(goal).ok_or(PathError::NoPath)?;
+    let ends = [(start_polygon, start), (goal_polygon, goal)];
+
+    //Find the polygons to pass through, travelling via the middle of each shared edge
+    let polygons: Route<f64, usize> = crate::a_star(
+        start_polygon,
+        goal_polygon,
+        |polygon| {
+            let from = mesh.waypoint(polygon, ends);
+            mesh.portals[polygon].iter().map(move |&(next, a, b)| {
+                let (a, b) = (mesh.vertices[a], mesh.vertices[b]);
+                let middle = ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+                let to = mesh.waypoint(next, ends);
+                (next, length(from, middle) + length(middle, to))
+            })
+        },
+        |polygon| length(mesh.waypoint(polygon, ends), goal),
+        options,
+    )?;
+
+    let mut polygons = polygons.steps();
+    polygons.reverse();
+    if polygons.is_empty() {
+        polygons.push(start_polygon);
+    }
+
+    let mut path = funnel(&mesh.funnel_portals(&polygons, start, goal));
+    let distance = path.windows(2).map(|pair| length(pair[0], pair[1])).sum();
+    path.reverse();
+
+    Ok(Route::from((distance, path)))
+}
+
+//The simple stupid funnel algorithm. Returns the corners of the shortest path through a list of
+//portals, the first and last of which are the start and goal.
+fn funnel(portals: &[(Point, Point)]) -> Vec<Point> {
+    let mut path = vec![portals[0].0];
+    let (mut apex, mut left, mut right) = (portals[0].0, portals[0].0, portals[0].1);
+    let (mut left_index, mut right_index) = (0, 0);
+
+    let mut index = 1;
+    while index < portals.len() {
+        let (next_left, next_right) = portals[index];
+
+        //Tighten the right side of the funnel, unless it crosses over the left
+        if cross(apex, right, next_right) >= 0.0 {
+            if apex == right || cross(apex, left, next_right) < 0.0 {
+                right = next_right;
+                right_index = index;
+            } else {
+                //The left side becomes a corner of the path
+                path.push(left);
+                apex = left;
+                right = left;
+                right_index = left_index;
+                index = left_index + 1;
+                continue;
+            }
+        }
+
+        //Tighten the left side of the funnel, unless it crosses over the right
+        if cross(apex, left, next_left) <= 0.0 {
+            if apex == left || cross(apex, right, next_left) > 0.0 {
+                left = next_left;
+                left_index = index;
+            } else {
+                //The right side becomes a corner of the path
+                path.push(right);
+                apex = right;
+                left = right;
+                left_index = right_index;
+                index = right_index + 1;
+                continue;
+            }
+        }
+
+        index += 1;
+    }
+
+    let goal = portals[portals.len() - 1].0;
+    if path.last() != Some(&goal) {
+        path.push(goal);
+    }
+
+    path
+}
+
+//Pairs of neighbouring vertices around a polygon
+fn edges_of(polygon: &[usize]) -> impl Iterator<Item = (usize, usize)> + '_ {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(a, b)| (*a, *b))
+}
+
+//Positive when c is to the left of the line from a to b, i.e. anticlockwise of it
+fn cross(a: Point, b: Point, c: Point) -> f64 {
+    (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
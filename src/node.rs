@@ -1,21 +1,22 @@
 use movingai::Coords2D;
 use std::cmp::Ordering;
 
-use crate::utils::distance;
+use crate::cost::Cost;
+use crate::options::TieBreak;
 
 #[derive(Debug, Copy, Clone)]
-pub struct Node {
-    pub f: f64,
-    pub g: f64,
-    pub h: f64,
-    pub position: Coords2D,
-    pub parent: Coords2D,
+pub struct Node<N, C> {
+    pub f: C,
+    pub g: C,
+    pub h: C,
+    pub position: N,
+    pub parent: N,
 }
 
-impl Node {
-    pub fn new(g: f64, h: f64, position: Coords2D, parent: Coords2D) -> Node {
+impl<N, C: Cost> Node<N, C> {
+    pub fn new(g: C, h: C, position: N, parent: N) -> Node<N, C> {
         Node {
-            f: g + h,
+            f: g.plus(h),
             g,
             h,
             position,
@@ -23,42 +24,60 @@ impl Node {
         }
     }
 
-    pub fn from_parent(parent: &Node, position: Coords2D, goal: Coords2D) -> Node {
+    ///Checks that none of the node's costs are NaN, infinite or negative.
+    pub fn is_valid(&self) -> bool {
+        self.f.is_valid() && self.g.is_valid() && self.h.is_valid()
+    }
+}
+
+impl<C: Cost> Node<Coords2D, C> {
+    pub fn from_parent(parent: &Node<Coords2D, C>, position: Coords2D, goal: Coords2D) -> Self {
         //Calculate distances
-        let distance_to_goal = distance(position, goal);
-        let distance_from_parent = distance(parent.position, position);
-        let total_distance = parent.g + distance_from_parent;
+        let distance_to_goal = C::distance(position, goal);
+        let distance_from_parent = C::distance(parent.position, position);
+        let total_distance = parent.g.plus(distance_from_parent);
 
         //Build node from distances
         Node {
-            f: total_distance + distance_to_goal,
+            f: total_distance.plus(distance_to_goal),
             g: total_distance,
             h: distance_to_goal,
-            position: position,
+            position,
             parent: parent.position,
         }
     }
 }
 
-impl PartialOrd for Node {
-    fn partial_cmp(&self, other: &Node) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Ord for Node {
-    fn cmp(&self, other: &Node) -> Ordering {
-        // This is reversed on purpose to make the max-heap into min-heap.
-        other.f.partial_cmp(&self.f).unwrap()
+impl<N: Ord, C: Cost> Node<N, C> {
+    ///Orders nodes so that the node to expand first is the greatest, as the open list is a max-heap.
+    ///`positions` orders the nodes' positions for TieBreak::Position, which searches numbering their
+    ///nodes use to compare the nodes' coordinates instead.
+    #[inline]
+    pub fn compare<F: Fn(&N, &N) -> Ordering>(
+        &self,
+        other: &Node<N, C>,
+        tie_break: TieBreak,
+        positions: &F,
+    ) -> Ordering {
+        other
+            .f
+            .total_cmp(&self.f)
+            .then_with(|| match tie_break {
+                TieBreak::HigherG => self.g.total_cmp(&other.g),
+                TieBreak::LowerH => other.h.total_cmp(&self.h),
+                TieBreak::Position => positions(&other.position, &self.position),
+            })
+            .then_with(|| other.position.cmp(&self.position))
+            .then_with(|| other.parent.cmp(&self.parent))
     }
 }
 
-impl PartialEq for Node {
-    fn eq(&self, other: &Node) -> bool {
+impl<N: PartialEq, C> PartialEq for Node<N, C> {
+    fn eq(&self, other: &Node<N, C>) -> bool {
         self.position == other.position
     }
 }
 
-impl Eq for Node {
+impl<N: Eq, C> Eq for Node<N, C> {
     // add code here
 }
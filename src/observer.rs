@@ -0,0 +1,29 @@
+use movingai::Coords2D;
+
+///Receives events from a search as it runs, such as to animate the search frontier in an editor.
+///
+///Passed to [`a_star_path_observed`](crate::a_star_path_observed) and
+///[`jps_path_observed`](crate::jps_path_observed). Every method does nothing by default, so only the
+///events of interest need to be implemented. Distances are converted to `f64` whatever Cost type the
+///search uses.
+pub trait SearchObserver<N = Coords2D> {
+    ///Called when a node is pushed to the open list, with the node it was reached from, its distance
+    ///from the start and its estimated distance to the goal. The start's parent is itself.
+    fn pushed(&mut self, _node: N, _parent: N, _g: f64, _h: f64) {}
+
+    ///Called by JPS when a jump point is found, before it is pushed. A* never calls this.
+    fn jumped(&mut self, _node: N) {}
+
+    ///Called when a node is taken from the open list, before its successors are pushed.
+    fn expanded(&mut self, _node: N) {}
+
+    ///Called once all of a node's successors have been pushed, after which it is not expanded again
+    ///unless A* finds it by a shorter route.
+    fn closed(&mut self, _node: N) {}
+
+    ///Called when the goal is reached, with the steps of the route, goal first, and its distance.
+    fn found(&mut self, _steps: &[N], _distance: f64) {}
+}
+
+//Observer used by searches which are not being observed
+impl<N> SearchObserver<N> for () {}
@@ -0,0 +1,93 @@
+use std::cmp::Ordering;
+
+use crate::cost::Cost;
+use crate::heap::Heap;
+use crate::node::Node;
+use crate::options::TieBreak;
+use crate::radix::RadixHeap;
+
+///Open list used by the search algorithms.
+///Nodes with integer costs are queued in a radix heap, all others in a binary heap. Integer
+///keys which are smaller than the last key popped, as an inconsistent heuristic can produce, also
+///go in the binary heap so that nodes are never popped out of order.
+///
+///The tie-break policy is kept here rather than in each node, to keep nodes small.
+#[derive(Debug)]
+pub struct OpenList<N, C> {
+    heap: Heap<Node<N, C>>,
+    radix: RadixHeap<Node<N, C>>,
+    tie_break: TieBreak,
+}
+
+impl<N: Ord, C: Cost> OpenList<N, C> {
+    pub fn with_capacity(capacity: usize, tie_break: TieBreak) -> OpenList<N, C> {
+        OpenList {
+            heap: Heap::with_capacity(capacity),
+            radix: RadixHeap::new(),
+            tie_break,
+        }
+    }
+
+    ///Changes the tie-break policy. The list must be empty.
+    pub fn set_tie_break(&mut self, tie_break: TieBreak) {
+        debug_assert_eq!(self.len(), 0);
+        self.tie_break = tie_break;
+    }
+
+    #[inline]
+    pub fn push(&mut self, node: Node<N, C>) {
+        self.push_by(node, &N::cmp);
+    }
+
+    #[inline]
+    pub fn pop(&mut self) -> Option<Node<N, C>> {
+        self.pop_by(&N::cmp)
+    }
+
+    ///Pushes a node, ordering positions with the given function for TieBreak::Position.
+    ///The same function must be used for every push and pop.
+    #[inline]
+    pub fn push_by<F: Fn(&N, &N) -> Ordering>(&mut self, node: Node<N, C>, positions: &F) {
+        let tie_break = self.tie_break;
+        let cmp = |a: &Node<N, C>, b: &Node<N, C>| a.compare(b, tie_break, positions);
+
+        match node.f.radix_key() {
+            Some(key) if key >= self.radix.last() => self.radix.push(key, node, &cmp),
+            _ => self.heap.push(node, &cmp),
+        }
+    }
+
+    ///Pops the next node, ordering positions with the given function for TieBreak::Position.
+    #[inline]
+    pub fn pop_by<F: Fn(&N, &N) -> Ordering>(&mut self, positions: &F) -> Option<Node<N, C>> {
+        let tie_break = self.tie_break;
+        let cmp = |a: &Node<N, C>, b: &Node<N, C>| a.compare(b, tie_break, positions);
+
+        let radix_first = match (self.radix.peek(&cmp), self.heap.peek()) {
+            (Some(radix), Some(heap)) => cmp(radix, heap) != Ordering::Less,
+            (radix, _) => radix.is_some(),
+        };
+
+        if radix_first {
+            self.radix.pop(&cmp)
+        } else {
+            self.heap.pop(&cmp)
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.heap.clear();
+        self.radix.clear();
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.heap.len() + self.radix.len()
+    }
+
+    pub fn into_vec(self) -> Vec<Node<N, C>> {
+        let mut nodes = self.heap.into_vec();
+        nodes.append(&mut self.radix.into_vec());
+        nodes
+    }
+}
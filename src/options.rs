@@ -0,0 +1,80 @@
+use movingai::Coords2D;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+///Decides which node is expanded first when two nodes have the same estimated total distance.
+///
+///Every rule falls back to comparing positions and then parents, so nodes are always popped from the
+///open list in the same order and a search always returns the same Route, whichever heap is used.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TieBreak {
+    ///Prefer the node furthest from the start. This usually expands the fewest nodes.
+    #[default]
+    HigherG,
+    ///Prefer the node with the lowest estimated distance to the goal.
+    LowerH,
+    ///Prefer the node with the lowest coordinates, comparing x and then y.
+    ///Searches of other graphs, whose nodes need not have coordinates, prefer the node discovered
+    ///first instead.
+    Position,
+}
+
+///Options used to configure a search.
+///
+///More options may be added later, so start from `SearchOptions::default()` and set the fields needed.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub struct SearchOptions {
+    ///How to choose between nodes with equal estimated total distance.
+    pub tie_break: TieBreak,
+}
+
+///The moves allowed between consecutive steps of a route.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Neighbourhood {
+    ///Moves to the four tiles sharing an edge.
+    Four,
+    ///Moves to all eight surrounding tiles, moving diagonally only when both of the straight steps
+    ///making up the diagonal are passable. These are the moves made by A*.
+    #[default]
+    Eight,
+    ///Moves to all eight surrounding tiles, including diagonal moves past impassable tiles.
+    EightCuttingCorners,
+    ///Moves in a straight line to any tile in [`line_of_sight`](crate::line_of_sight), as made by
+    ///simplified routes.
+    AnyAngle,
+}
+
+///Rules used to validate a route against a map.
+///
+///More rules may be added later, so start from `ValidationRules::default()` and set the fields needed.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub struct ValidationRules {
+    ///The moves allowed between consecutive steps.
+    pub neighbourhood: Neighbourhood,
+    ///Where the route must start, if anywhere in particular.
+    pub start: Option<Coords2D>,
+    ///Where the route must end, if anywhere in particular.
+    pub goal: Option<Coords2D>,
+    ///How far the stored distance may differ from the measured distance, allowing for rounding.
+    ///This is relative to the measured distance, or absolute for distances shorter than one. Routes
+    ///with floating-point costs are also allowed the rounding error of adding up each step, so long
+    ///routes measured in `f32` are not rejected.
+    pub tolerance: f64,
+}
+
+impl Default for ValidationRules {
+    fn default() -> Self {
+        ValidationRules {
+            neighbourhood: Neighbourhood::default(),
+            start: None,
+            goal: None,
+            tolerance: 1e-6,
+        }
+    }
+}
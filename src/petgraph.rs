@@ -0,0 +1,405 @@
+//!Searches over [petgraph](https://docs.rs/petgraph) graphs, enabled by the `petgraph` feature.
+//!
+//!Each search works on a reference to a `Graph` or `StableGraph`, using the edge weights as the cost
+//!of each step, and returns a Route whose steps are the node indices of the path. Edge weights must be
+//!a [`Cost`] type which is valid, i.e. finite and not negative.
+
+use std::hash::Hash;
+use std::ops::Sub;
+
+use ::petgraph::visit::{Data, EdgeRef, IntoEdges, IntoEdgesDirected, NodeIndexable};
+use ::petgraph::Direction;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+
+use crate::cost::Cost;
+use crate::node::Node;
+use crate::open::OpenList;
+use crate::{PathError, Route, SearchOptions, TieBreak};
+
+///Creates a new route between two nodes of a graph using the A* algorithm.
+///
+///`heuristic` estimates the cost of travelling from a node to the goal, and must never overestimate
+///it for the route to be the shortest. A heuristic which always returns zero gives Dijkstra's
+///algorithm.
+/// # Examples
+///
+/// ```
+/// use blitz_path::{Route, SearchOptions};
+/// use petgraph::graph::UnGraph;
+///
+/// let graph = UnGraph::<(), u32>::from_edges(&[(0, 1, 4), (0, 2, 1), (2, 1, 2), (1, 3, 5)]);
+/// let start = 0.into();
+/// let goal = 3.into();
+///
+/// let route = blitz_path::petgraph::a_star(&graph, start, goal, |_| 0, &SearchOptions::default())?;
+///
+/// assert_eq!(route.distance(), 8);
+/// assert_eq!(route.steps(), vec![goal, 1.into(), 2.into(), start]);
+/// # Ok::<(), blitz_path::PathError>(())
+/// ```
+pub fn a_star<G, C, F>(
+    graph: G,
+    start: G::NodeId,
+    goal: G::NodeId,
+    heuristic: F,
+    options: &SearchOptions,
+) -> Result<Route<C, G::NodeId>, PathError>
+where
+    G: IntoEdges + Data<EdgeWeight = C>,
+    G::NodeId: Eq + Hash,
+    C: Cost,
+    F: FnMut(G::NodeId) -> C,
+{
+    crate::a_star(
+        start,
+        goal,
+        |node| {
+            graph
+                .edges(node)
+                .map(move |edge| (other(&edge, node), *edge.weight()))
+        },
+        heuristic,
+        options,
+    )
+}
+
+///Creates a new route between two nodes of a graph by searching from both ends at once.
+///
+///A search is run outwards from the start and backwards from the goal, following edges against their
+///direction, until the two meet. This needs no heuristic and usually expands far fewer nodes than
+///searching from the start alone.
+/// # Examples
+///
+/// ```
+/// use blitz_path::SearchOptions;
+/// use petgraph::graph::DiGraph;
+///
+/// let graph = DiGraph::<(), f64>::from_edges(&[(0, 1, 1.0), (1, 2, 1.0), (0, 2, 3.0), (2, 3, 1.0)]);
+///
+/// let route = blitz_path::petgraph::bidirectional(&graph, 0.into(), 3.into(), &SearchOptions::default())?;
+///
+/// assert_eq!(route.distance(), 3.0);
+/// assert_eq!(route.steps(), vec![3.into(), 2.into(), 1.into(), 0.into()]);
+/// # Ok::<(), blitz_path::PathError>(())
+/// ```
+pub fn bidirectional<G, C>(
+    graph: G,
+    start: G::NodeId,
+    goal: G::NodeId,
+    options: &SearchOptions,
+) -> Result<Route<C, G::NodeId>, PathError>
+where
+    G: IntoEdgesDirected + NodeIndexable + Data<EdgeWeight = C>,
+    C: Cost,
+{
+    if start == goal {
+        return Ok(Route::from((C::zero(), vec![])));
+    }
+
+    let size = graph.node_bound();
+    let mut frontiers = [
+        Frontier::new(size, graph.to_index(start), options.tie_break),
+        Frontier::new(size, graph.to_index(goal), options.tie_break),
+    ];
+
+    //Shortest route found so far and the node where both searches met on it
+    let mut best: Option<(C, usize)> = None;
+
+    //Alternate between the searches, stopping once neither can find a shorter route
+    for side in (0..2).cycle() {
+        let (forward, backward) = frontiers.split_at_mut(1);
+        let (frontier, other, direction) = match side {
+            0 => (&mut forward[0], &backward[0], Direction::Outgoing),
+            _ => (&mut backward[0], &forward[0], Direction::Incoming),
+        };
+
+        let settled = frontier.settle(graph, direction, |index, g| {
+            if let Some(other_g) = other.g[index] {
+                let total = g.plus(other_g);
+                if best.is_none_or(|(distance, _)| total < distance) {
+                    best = Some((total, index));
+                }
+            }
+        })?;
+
+        let finished = match (settled, best) {
+            (None, _) => true,
+            (Some(_), Some((distance, _))) => frontier.last.plus(other.last) >= distance,
+            (Some(_), None) => false,
+        };
+        if finished {
+            break;
+        }
+    }
+
+    let (distance, meeting) = best.ok_or(PathError::NoPath)?;
+
+    //Steps run from the goal to the meeting node, then on to the start
+    let mut path = frontiers[1].rewind(graph, meeting);
+    path.reverse();
+    path.extend(frontiers[0].rewind(graph, meeting).into_iter().skip(1));
+
+    Ok(Route::from((distance, path)))
+}
+
+///Creates a new route between two nodes of a graph using the A* algorithm, with a heuristic calculated
+///from the distances to and from a set of landmarks.
+///
+///This is also known as ALT. Finding the distances to the landmarks is slow, but only needs doing
+///once for a graph, after which the heuristic is usually much better than one based on positions.
+/// # Examples
+///
+/// ```
+/// use blitz_path::petgraph::Landmarks;
+/// use blitz_path::SearchOptions;
+/// use petgraph::graph::UnGraph;
+///
+/// let graph = UnGraph::<(), u32>::from_edges(&[(0, 1, 4), (0, 2, 1), (2, 1, 2), (1, 3, 5)]);
+/// let landmarks = Landmarks::new(&graph, &[3.into()])?;
+///
+/// let route = blitz_path::petgraph::landmark(&graph, &landmarks, 0.into(), 3.into(), &SearchOptions::default())?;
+///
+/// assert_eq!(route.distance(), 8);
+/// # Ok::<(), blitz_path::PathError>(())
+/// ```
+pub fn landmark<G, C>(
+    graph: G,
+    landmarks: &Landmarks<C>,
+    start: G::NodeId,
+    goal: G::NodeId,
+    options: &SearchOptions,
+) -> Result<Route<C, G::NodeId>, PathError>
+where
+    G: IntoEdges + NodeIndexable + Data<EdgeWeight = C>,
+    G::NodeId: Eq + Hash,
+    C: Cost + Sub<Output = C>,
+{
+    let goal_index = graph.to_index(goal);
+
+    a_star(
+        graph,
+        start,
+        goal,
+        |node| landmarks.heuristic(graph.to_index(node), goal_index),
+        options,
+    )
+}
+
+///Distances between a set of landmark nodes and every other node of a graph, used by [`landmark`].
+///
+///Landmarks give the best heuristic when they are spread around the edges of the graph. The distances
+///are only correct for the graph they were found on, so must be found again if its edges change.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        try_from = "LandmarksData<C>",
+        bound(deserialize = "C: Cost + Deserialize<'de>")
+    )
+)]
+pub struct Landmarks<C> {
+    //Distance from each landmark to each node
+    from: Vec<Vec<Option<C>>>,
+    //Distance from each node to each landmark
+    to: Vec<Vec<Option<C>>>,
+}
+
+//Landmarks as loaded, before checking each landmark has a valid distance to and from every node
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct LandmarksData<C> {
+    from: Vec<Vec<Option<C>>>,
+    to: Vec<Vec<Option<C>>>,
+}
+
+#[cfg(feature = "serde")]
+impl<C: Cost> TryFrom<LandmarksData<C>> for Landmarks<C> {
+    type Error = String;
+
+    fn try_from(data: LandmarksData<C>) -> Result<Self, Self::Error> {
+        let size = data.from.first().map_or(0, Vec::len);
+        let lists = || data.from.iter().chain(data.to.iter());
+        if data.from.len() != data.to.len() || lists().any(|list| list.len() != size) {
+            return Err(String::from(
+                "landmarks need a distance to and from every node",
+            ));
+        }
+        if !lists()
+            .flatten()
+            .flatten()
+            .all(|distance| distance.is_valid())
+        {
+            return Err(String::from("landmark distance is not valid"));
+        }
+
+        Ok(Landmarks {
+            from: data.from,
+            to: data.to,
+        })
+    }
+}
+
+impl<C: Cost + Sub<Output = C>> Landmarks<C> {
+    ///Finds the distances between each landmark and every node of a graph.
+    ///Returns a PathError if an edge weight is invalid.
+    pub fn new<G>(graph: G, landmarks: &[G::NodeId]) -> Result<Landmarks<C>, PathError>
+    where
+        G: IntoEdgesDirected + NodeIndexable + Data<EdgeWeight = C>,
+    {
+        let size = graph.node_bound();
+        let mut from = Vec::with_capacity(landmarks.len());
+        let mut to = Vec::with_capacity(landmarks.len());
+
+        for landmark in landmarks {
+            for (distances, direction) in [
+                (&mut from, Direction::Outgoing),
+                (&mut to, Direction::Incoming),
+            ] {
+                let mut frontier =
+                    Frontier::new(size, graph.to_index(*landmark), TieBreak::default());
+                while frontier.settle(graph, direction, |_, _| {})?.is_some() {}
+                distances.push(frontier.g);
+            }
+        }
+
+        Ok(Landmarks { from, to })
+    }
+
+    ///Returns the number of landmarks.
+    pub fn len(&self) -> usize {
+        self.from.len()
+    }
+
+    ///Whether there are no landmarks, in which case searches fall back to Dijkstra's algorithm.
+    pub fn is_empty(&self) -> bool {
+        self.from.is_empty()
+    }
+
+    //Lower bound on the distance between two nodes, using the triangle inequality
+    fn heuristic(&self, node: usize, goal: usize) -> C {
+        let mut best = C::zero();
+
+        for (from, to) in self.from.iter().zip(self.to.iter()) {
+            let bounds = [
+                (from.get(goal), from.get(node)),
+                (to.get(node), to.get(goal)),
+            ];
+
+            for (far, near) in bounds {
+                if let (Some(Some(far)), Some(Some(near))) = (far, near) {
+                    if *far > *near && *far - *near > best {
+                        best = *far - *near;
+                    }
+                }
+            }
+        }
+
+        best
+    }
+}
+
+//One direction of a Dijkstra search, with nodes identified by their index in the graph
+struct Frontier<C> {
+    g: Vec<Option<C>>,
+    parent: Vec<usize>,
+    closed: Vec<bool>,
+    open: OpenList<usize, C>,
+    last: C,
+}
+
+impl<C: Cost> Frontier<C> {
+    fn new(size: usize, source: usize, tie_break: TieBreak) -> Frontier<C> {
+        let mut frontier = Frontier {
+            g: vec![None; size],
+            parent: (0..size).collect(),
+            closed: vec![false; size],
+            open: OpenList::with_capacity(64, tie_break),
+            last: C::zero(),
+        };
+
+        frontier.g[source] = Some(C::zero());
+        frontier
+            .open
+            .push(Node::new(C::zero(), C::zero(), source, source));
+
+        frontier
+    }
+
+    //Settles the nearest node which is not yet settled and returns its index, or None once every
+    //reachable node is settled. `labelled` is called with each node given a shorter distance.
+    fn settle<G, F>(
+        &mut self,
+        graph: G,
+        direction: Direction,
+        mut labelled: F,
+    ) -> Result<Option<usize>, PathError>
+    where
+        G: IntoEdgesDirected + NodeIndexable + Data<EdgeWeight = C>,
+        F: FnMut(usize, C),
+    {
+        while let Some(node_current) = self.open.pop() {
+            let current = node_current.position;
+            if self.closed[current] {
+                continue;
+            }
+            self.closed[current] = true;
+            self.last = node_current.g;
+
+            let node = graph.from_index(current);
+            for edge in graph.edges_directed(node, direction) {
+                let step = *edge.weight();
+                if !step.is_valid() {
+                    return Err(PathError::InvalidCost);
+                }
+
+                let next = graph.to_index(other(&edge, node));
+                let total_distance = node_current.g.plus(step);
+                if self.g[next].is_some_and(|g| g <= total_distance) {
+                    continue;
+                }
+
+                let node_successor = Node::new(total_distance, C::zero(), next, current);
+                if !node_successor.is_valid() {
+                    return Err(PathError::InvalidCost);
+                }
+
+                self.g[next] = Some(total_distance);
+                self.parent[next] = current;
+                labelled(next, total_distance);
+                self.open.push(node_successor);
+            }
+
+            return Ok(Some(current));
+        }
+
+        Ok(None)
+    }
+
+    //Nodes from `index` back to the source of the search
+    fn rewind<G: NodeIndexable>(&self, graph: G, mut index: usize) -> Vec<G::NodeId> {
+        let mut path = vec![graph.from_index(index)];
+
+        while self.parent[index] != index {
+            index = self.parent[index];
+            path.push(graph.from_index(index));
+        }
+
+        path
+    }
+}
+
+//The node at the other end of an edge, which works for both directed and undirected graphs
+fn other<E: EdgeRef>(edge: &E, node: E::NodeId) -> E::NodeId
+where
+    E::NodeId: PartialEq,
+{
+    if edge.source() == node {
+        edge.target()
+    } else {
+        edge.source()
+    }
+}
@@ -0,0 +1,117 @@
+use std::cmp::Ordering;
+
+use crate::heap::Heap;
+
+///A monotone priority queue for integer keys.
+///
+///Items are stored in buckets based on the highest bit in which their key differs from the last
+///key popped, so each item is moved between buckets at most 64 times. Keys must never be smaller
+///than the last key popped, which holds for A* with a consistent heuristic. Callers should check
+///`last` and queue smaller keys elsewhere.
+///
+///Items with equal keys are popped greatest first, according to the comparison given to each call.
+#[derive(Debug)]
+pub struct RadixHeap<T> {
+    last: u64,
+    len: usize,
+    //Items whose key equals `last`, kept ordered so ties are broken in O(log n)
+    first: Heap<T>,
+    buckets: Vec<Vec<(u64, T)>>,
+}
+
+impl<T> RadixHeap<T> {
+    pub fn new() -> RadixHeap<T> {
+        RadixHeap {
+            last: 0,
+            len: 0,
+            first: Heap::new(),
+            buckets: Vec::new(),
+        }
+    }
+
+    ///The last key popped. No smaller key may be pushed.
+    #[inline]
+    pub fn last(&self) -> u64 {
+        self.last
+    }
+
+    pub fn push<F: Fn(&T, &T) -> Ordering>(&mut self, key: u64, item: T, cmp: &F) {
+        debug_assert!(key >= self.last, "radix heap key went backwards");
+
+        if key == self.last {
+            self.first.push(item, cmp);
+        } else {
+            if self.buckets.is_empty() {
+                self.buckets.resize_with(65, Vec::new);
+            }
+            self.buckets[bucket(key, self.last)].push((key, item));
+        }
+        self.len += 1;
+    }
+
+    ///Returns the item which would be popped next.
+    pub fn peek<F: Fn(&T, &T) -> Ordering>(&mut self, cmp: &F) -> Option<&T> {
+        if self.first.is_empty() {
+            self.refill(cmp);
+        }
+        self.first.peek()
+    }
+
+    pub fn pop<F: Fn(&T, &T) -> Ordering>(&mut self, cmp: &F) -> Option<T> {
+        if self.first.is_empty() {
+            self.refill(cmp);
+        }
+
+        let item = self.first.pop(cmp)?;
+        self.len -= 1;
+        Some(item)
+    }
+
+    //Move the smallest keys from the next non-empty bucket into the first bucket
+    fn refill<F: Fn(&T, &T) -> Ordering>(&mut self, cmp: &F) {
+        let index = match self.buckets.iter().position(|x| !x.is_empty()) {
+            Some(index) => index,
+            None => return,
+        };
+        let items = std::mem::take(&mut self.buckets[index]);
+        self.last = items.iter().map(|x| x.0).min().unwrap_or(self.last);
+
+        for (key, item) in items {
+            if key == self.last {
+                self.first.push(item, cmp);
+            } else {
+                self.buckets[bucket(key, self.last)].push((key, item));
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.last = 0;
+        self.len = 0;
+        self.first.clear();
+        for bucket in self.buckets.iter_mut() {
+            bucket.clear();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        let mut items = self.first.into_vec();
+        items.extend(self.buckets.into_iter().flatten().map(|x| x.1));
+        items
+    }
+}
+
+impl<T> Default for RadixHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[inline]
+fn bucket(key: u64, last: u64) -> usize {
+    (64 - (key ^ last).leading_zeros()) as usize
+}
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+
+use movingai::Coords2D;
+
+#[cfg(feature = "observer")]
+use crate::SearchObserver;
+use crate::{Grid, Route};
+
+//Colours of each kind of tile in PPM images
+const PASSABLE: [u8; 3] = [255, 255, 255];
+const BLOCKED: [u8; 3] = [0, 0, 0];
+const EXPANDED: [u8; 3] = [160, 200, 255];
+const JUMP_POINT: [u8; 3] = [255, 160, 0];
+const ROUTE: [u8; 3] = [220, 0, 0];
+const START: [u8; 3] = [0, 180, 0];
+const GOAL: [u8; 3] = [0, 0, 220];
+
+///Tiles to draw over a grid by [`render_ascii`] and [`render_ppm`].
+///
+///When the `observer` feature is enabled the overlay is also a `SearchObserver`, recording the nodes
+///expanded by a search, the jump points found by JPS and the route it finds.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Overlay {
+    ///Steps of a route, goal first as in [`Route::as_slice`].
+    pub route: Vec<Coords2D>,
+    ///Nodes expanded by a search.
+    pub expanded: Vec<Coords2D>,
+    ///Jump points found by JPS.
+    pub jump_points: Vec<Coords2D>,
+    ///A start to mark without a route, such as when a search found no route.
+    pub start: Option<Coords2D>,
+    ///A goal to mark without a route, such as when a search found no route.
+    pub goal: Option<Coords2D>,
+    ///The first and last tiles of the area to draw, or None to draw the whole grid.
+    pub area: Option<(Coords2D, Coords2D)>,
+}
+
+impl Overlay {
+    ///Creates an overlay showing a route.
+    pub fn from_route<C>(route: &Route<C>) -> Overlay {
+        Overlay {
+            route: route.as_slice().to_vec(),
+            ..Overlay::default()
+        }
+    }
+
+    ///Returns the smallest area containing every tile of the overlay, grown by `margin` tiles on each
+    ///side, or None if the overlay is empty.
+    pub fn bounds(&self, margin: usize) -> Option<(Coords2D, Coords2D)> {
+        let mut tiles = self
+            .route
+            .iter()
+            .chain(self.expanded.iter())
+            .chain(self.jump_points.iter())
+            .chain(self.start.iter())
+            .chain(self.goal.iter());
+        let first = *tiles.next()?;
+
+        let (min, max) = tiles.fold((first, first), |(min, max), tile| {
+            (
+                (min.0.min(tile.0), min.1.min(tile.1)),
+                (max.0.max(tile.0), max.1.max(tile.1)),
+            )
+        });
+
+        Some((
+            (min.0.saturating_sub(margin), min.1.saturating_sub(margin)),
+            (max.0 + margin, max.1 + margin),
+        ))
+    }
+
+    //Finds the mark to draw on each tile, with later marks drawn over earlier ones
+    fn marks(&self) -> HashMap<Coords2D, Mark> {
+        let mut marks = HashMap::new();
+        marks.extend(self.expanded.iter().map(|tile| (*tile, Mark::Expanded)));
+        marks.extend(self.jump_points.iter().map(|tile| (*tile, Mark::JumpPoint)));
+        marks.extend(self.route.iter().map(|tile| (*tile, Mark::Route)));
+        marks.extend(self.route.first().map(|tile| (*tile, Mark::Goal)));
+        marks.extend(self.route.last().map(|tile| (*tile, Mark::Start)));
+        marks.extend(self.goal.map(|tile| (tile, Mark::Goal)));
+        marks.extend(self.start.map(|tile| (tile, Mark::Start)));
+
+        marks
+    }
+
+    //Rows and columns of the area to draw, clipped to the grid, or None if no tiles are inside it
+    fn area<G: Grid>(&self, map: &G) -> Option<(Coords2D, Coords2D)> {
+        let (first, last) = self.area.unwrap_or(((0, 0), (usize::MAX, usize::MAX)));
+        let last = (
+            last.0.min(map.width().checked_sub(1)?),
+            last.1.min(map.height().checked_sub(1)?),
+        );
+
+        if first.0 > last.0 || first.1 > last.1 {
+            None
+        } else {
+            Some((first, last))
+        }
+    }
+}
+
+#[cfg(feature = "observer")]
+impl SearchObserver for Overlay {
+    fn jumped(&mut self, node: Coords2D) {
+        self.jump_points.push(node);
+    }
+
+    fn expanded(&mut self, node: Coords2D) {
+        self.expanded.push(node);
+    }
+
+    fn found(&mut self, steps: &[Coords2D], _distance: f64) {
+        self.route = steps.to_vec();
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Mark {
+    Start,
+    Goal,
+    Route,
+    JumpPoint,
+    Expanded,
+}
+
+///Draws a grid as text, one character per tile, with a line for each row.
+///
+///Passable tiles are drawn as `.` and impassable tiles as `@`, as in `.map` files. The route is drawn
+///as `*`, with its start as `S` and goal as `G`, then jump points as `j` and expanded nodes as `x`.
+/// # Examples
+///
+/// ```
+/// use blitz_path::{render_ascii, BitGrid, Overlay};
+///
+/// let mut map = BitGrid::new(4, 2);
+/// for x in 0..3 {
+///     map.set_passable((x, 0), true);
+/// }
+///
+/// let overlay = Overlay {
+///     route: vec![(2, 0), (1, 0), (0, 0)],
+///     ..Overlay::default()
+/// };
+///
+/// assert_eq!(render_ascii(&map, &overlay), "S*G@\n@@@@\n");
+/// ```
+pub fn render_ascii<G: Grid>(map: &G, overlay: &Overlay) -> String {
+    let mut text = String::new();
+    let (first, last) = match overlay.area(map) {
+        Some(area) => area,
+        None => return text,
+    };
+    let marks = overlay.marks();
+
+    for y in first.1..=last.1 {
+        for x in first.0..=last.0 {
+            let tile = (x, y);
+            text.push(match marks.get(&tile) {
+                Some(Mark::Start) => 'S',
+                Some(Mark::Goal) => 'G',
+                Some(Mark::Route) => '*',
+                Some(Mark::JumpPoint) => 'j',
+                Some(Mark::Expanded) => 'x',
+                None if map.is_passable(tile) => '.',
+                None => '@',
+            });
+        }
+        text.push('\n');
+    }
+
+    text
+}
+
+///Draws a grid as a binary PPM image, one pixel per tile, which most image viewers and editors can
+///open.
+///
+///Passable tiles are white and impassable tiles black. The route is red, with its start green and goal
+///blue, jump points are orange and expanded nodes light blue.
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use std::path::Path;
+/// use blitz_path::{render_ppm, Overlay};
+///
+/// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+/// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+/// let scene = &scenes[0];
+///
+/// if let Some(path) = blitz_path::a_star_path(&map, scene.start_pos, scene.goal_pos) {
+///     let image = render_ppm(&map, &Overlay::from_route(&path));
+///
+///     assert!(image.starts_with(b"P6\n512 512\n255\n"));
+///     //std::fs::write("route.ppm", image)?;
+/// }
+/// # Ok::<(), io::Error>(())
+/// ```
+pub fn render_ppm<G: Grid>(map: &G, overlay: &Overlay) -> Vec<u8> {
+    let (first, last) = match overlay.area(map) {
+        Some(area) => area,
+        None => return b"P6\n0 0\n255\n".to_vec(),
+    };
+    let marks = overlay.marks();
+    let width = last.0 + 1 - first.0;
+    let height = last.1 + 1 - first.1;
+
+    let mut image = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    image.reserve(width * height * 3);
+
+    for y in first.1..=last.1 {
+        for x in first.0..=last.0 {
+            let tile = (x, y);
+            image.extend_from_slice(&match marks.get(&tile) {
+                Some(Mark::Start) => START,
+                Some(Mark::Goal) => GOAL,
+                Some(Mark::Route) => ROUTE,
+                Some(Mark::JumpPoint) => JUMP_POINT,
+                Some(Mark::Expanded) => EXPANDED,
+                None if map.is_passable(tile) => PASSABLE,
+                None => BLOCKED,
+            });
+        }
+    }
+
+    image
+}
@@ -1,16 +1,28 @@
 use std::convert::From;
+use std::iter::Rev;
+use std::ops::{Bound, Index, RangeBounds};
+use std::slice::Iter;
 
 use movingai::Coords2D;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{distance, line_of_sight, Cost, Grid, Point, Spline};
+use crate::{Neighbourhood, RouteError, ValidationRules};
 
 ///Describes a route between two points.
 ///Giving the total distance needed to travel and a vector of each step needed.
-pub struct Route {
-    distance: f64,
-    steps: Vec<Coords2D>,
+///The distance is measured using the Cost type of the search which created the route.
+///Steps are grid coordinates by default, but routes found on other graphs contain that graph's nodes.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Route<C = f64, N = Coords2D> {
+    distance: C,
+    steps: Vec<N>,
 }
 
-impl From<(f64, Vec<Coords2D>)> for Route {
-    fn from(item: (f64, Vec<Coords2D>)) -> Self {
+impl<C, N> From<(C, Vec<N>)> for Route<C, N> {
+    fn from(item: (C, Vec<N>)) -> Self {
         Route {
             distance: item.0,
             steps: item.1,
@@ -18,15 +30,403 @@ impl From<(f64, Vec<Coords2D>)> for Route {
     }
 }
 
-impl Route {
-    ///Returns a vector of Coords2D, each representing a step in the path.
+impl<C: Copy, N: Clone> Route<C, N> {
+    ///Returns a vector of steps, each representing a step in the path.
     ///Organised in reverse order (destination is at [0]) to allow calling .pop() to get each step.
-    pub fn steps(&self) -> Vec<Coords2D> {
+    ///This copies every step, so [`as_slice`](Route::as_slice) or [`iter`](Route::iter) are usually
+    ///better for reading them.
+    pub fn steps(&self) -> Vec<N> {
         self.steps.clone()
     }
 
-    ///Returns the total distance needed to travel as an f64.
-    pub fn distance(&self) -> f64 {
+    ///Returns the total distance needed to travel.
+    pub fn distance(&self) -> C {
         self.distance
     }
 }
+
+impl<C, N> Route<C, N> {
+    ///Returns the steps of the route, goal first, in the same order as [`steps`](Route::steps).
+    pub fn as_slice(&self) -> &[N] {
+        &self.steps
+    }
+
+    ///Returns an iterator over the steps of the route, from the goal back to the start.
+    pub fn iter(&self) -> Iter<'_, N> {
+        self.steps.iter()
+    }
+
+    ///Returns an iterator over the steps of the route, from the start to the goal.
+    pub fn iter_forward(&self) -> Rev<Iter<'_, N>> {
+        self.steps.iter().rev()
+    }
+
+    ///Consumes the route, returning its steps goal first without copying them.
+    pub fn into_steps(self) -> Vec<N> {
+        self.steps
+    }
+
+    ///Returns the number of steps in the route, including the start and goal.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    ///Whether the route has no steps, which is the case when the start is the goal.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    ///Returns the first step of the route, or None if it is empty.
+    pub fn start(&self) -> Option<&N> {
+        self.steps.last()
+    }
+
+    ///Returns the last step of the route, or None if it is empty.
+    pub fn goal(&self) -> Option<&N> {
+        self.steps.first()
+    }
+
+    ///Reverses the route in place, so it leads from the goal back to the start.
+    ///The distance is unchanged, so will not be correct if moving between two steps costs more in one
+    ///direction than the other, such as on a Grid with tile costs.
+    pub fn reverse(&mut self) {
+        self.steps.reverse();
+    }
+}
+
+///Indexes the steps of the route goal first, so `route[0]` is the goal.
+impl<C, N> Index<usize> for Route<C, N> {
+    type Output = N;
+
+    fn index(&self, index: usize) -> &N {
+        &self.steps[index]
+    }
+}
+
+impl<C: Cost, N: PartialEq> Route<C, N> {
+    ///Joins a route onto the end of this one, giving a route from this route's start to the other
+    ///route's goal with the sum of their distances.
+    ///Returns None unless the other route starts where this one ends. Empty routes can be joined to any
+    ///route, leaving it unchanged.
+    /// # Examples
+    ///
+    /// ```
+    /// use blitz_path::Route;
+    ///
+    /// let first = Route::from((2.0, vec![(2, 0), (1, 0), (0, 0)]));
+    /// let second = Route::from((1.0, vec![(2, 1), (2, 0)]));
+    /// let route = first.join(second).unwrap();
+    ///
+    /// assert_eq!(route.as_slice(), &[(2, 1), (2, 0), (1, 0), (0, 0)]);
+    /// assert_eq!(route.distance(), 3.0);
+    /// ```
+    pub fn join(self, next: Route<C, N>) -> Option<Route<C, N>> {
+        if next.steps.is_empty() {
+            return Some(self);
+        }
+        if self.steps.is_empty() {
+            return Some(next);
+        }
+        if self.goal() != next.start() {
+            return None;
+        }
+
+        //Steps are stored goal first, so the next route comes first, then this route without its goal
+        let mut steps = next.steps;
+        steps.extend(self.steps.into_iter().skip(1));
+
+        Some(Route::from((self.distance.plus(next.distance), steps)))
+    }
+}
+
+impl<C: Cost> Route<C> {
+    ///Returns the part of the route between two step indices, measuring its distance on a map.
+    ///Indices count from the goal, in the same order as [`as_slice`](Route::as_slice), and the distance
+    ///is measured in the same way as [`validate`](Route::validate).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is outside the route.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// use std::path::Path;
+    ///
+    /// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+    /// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+    /// let scene = &scenes[0];
+    ///
+    /// if let Some(path) = blitz_path::a_star_path(&map, scene.start_pos, scene.goal_pos) {
+    ///     //The route from the start to the step before the goal
+    ///     let part = path.sub_route(&map, 1..);
+    ///
+    ///     assert_eq!(part.start(), path.start());
+    ///     assert_eq!(part.goal(), Some(&path[1]));
+    ///     assert!(part.distance() < path.distance());
+    /// }
+    /// # Ok::<(), io::Error>(())
+    /// ```
+    pub fn sub_route<G: Grid, R: RangeBounds<usize>>(&self, map: &G, range: R) -> Route<C> {
+        let bounds: (Bound<usize>, Bound<usize>) =
+            (range.start_bound().cloned(), range.end_bound().cloned());
+        let steps = self.steps[bounds].to_vec();
+
+        Route::from((measure(map, &steps), steps))
+    }
+
+    ///Replaces the part of the route between the start and goal of another route with that route, such
+    ///as one replanned around a blocked step. The distance of the new route is measured on a map in the
+    ///same way as [`validate`](Route::validate).
+    ///Returns None if the replacement is empty, or does not start and end on this route with its start
+    ///before its goal.
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// use std::path::Path;
+    ///
+    /// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+    /// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+    /// let scene = &scenes[0];
+    ///
+    /// if let Some(path) = blitz_path::a_star_path(&map, scene.start_pos, scene.goal_pos) {
+    ///     //Replan the route from the start to the step before the goal
+    ///     let detour = blitz_path::a_star_path(&map, scene.start_pos, path[1]).unwrap();
+    ///     let spliced = path.splice(&map, &detour).unwrap();
+    ///
+    ///     assert_eq!(spliced.start(), path.start());
+    ///     assert_eq!(spliced.goal(), path.goal());
+    ///     assert!((spliced.distance() - path.distance()).abs() < 1e-9);
+    /// }
+    /// # Ok::<(), io::Error>(())
+    /// ```
+    pub fn splice<G: Grid>(&self, map: &G, replacement: &Route<C>) -> Option<Route<C>> {
+        let (start, goal) = (replacement.start()?, replacement.goal()?);
+        let to = self.steps.iter().position(|step| step == goal)?;
+        let from = to + self.steps[to..].iter().position(|step| step == start)?;
+
+        let mut steps = Vec::with_capacity(self.steps.len() - (from - to) + replacement.len());
+        steps.extend_from_slice(&self.steps[..to]);
+        steps.extend_from_slice(&replacement.steps);
+        steps.extend_from_slice(&self.steps[from + 1..]);
+
+        Some(Route::from((measure(map, &steps), steps)))
+    }
+
+    ///Returns a copy of the route with every step removed which can be skipped by moving in a straight
+    ///line, leaving only the waypoints where the route turns.
+    ///Waypoints are joined using [`line_of_sight`], so the simplified route never cuts corners the
+    ///original route could not. The distance is recalculated by measuring the straight line between each
+    ///waypoint in the route's Cost type, so does not include the costs of any tiles. Integer costs
+    ///round each of these lengths, rather than measuring octile distances.
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// use std::path::Path;
+    ///
+    /// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+    /// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+    /// let scene = &scenes[0];
+    ///
+    /// if let Some(path) = blitz_path::a_star_path(&map, scene.start_pos, scene.goal_pos) {
+    ///     let simple = path.simplify(&map);
+    ///
+    ///     assert!(simple.steps().len() <= path.steps().len());
+    ///     assert!(simple.distance() <= path.distance());
+    /// }
+    /// # Ok::<(), io::Error>(())
+    /// ```
+    pub fn simplify<G: Grid>(&self, map: &G) -> Route<C> {
+        let waypoints: Vec<Coords2D> = waypoints(map, &self.steps)
+            .into_iter()
+            .map(|index| self.steps[index])
+            .collect();
+
+        let distance = waypoints.windows(2).fold(C::zero(), |total, pair| {
+            total.plus(straight(pair[1], pair[0]))
+        });
+
+        Route::from((distance, waypoints))
+    }
+
+    ///Smooths the route into a continuous curve passing through the centre of each tile, starting at
+    ///the start of the route and ending at the goal.
+    ///The curve first passes through the waypoints of the simplified route, adding back steps from the
+    ///original route wherever it would cross an impassable tile, and moving straight between steps where
+    ///even that is not enough.
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// use std::path::Path;
+    ///
+    /// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+    /// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+    /// let scene = &scenes[0];
+    ///
+    /// if let Some(path) = blitz_path::a_star_path(&map, scene.start_pos, scene.goal_pos) {
+    ///     let spline = path.smooth(&map);
+    ///
+    ///     //Points one tile apart along the curve
+    ///     let points = spline.sample(1.0);
+    ///     assert_eq!(points.first(), Some(&(scene.start_pos.0 as f64, scene.start_pos.1 as f64)));
+    ///     assert_eq!(points.last(), Some(&(scene.goal_pos.0 as f64, scene.goal_pos.1 as f64)));
+    /// }
+    /// # Ok::<(), io::Error>(())
+    /// ```
+    pub fn smooth<G: Grid>(&self, map: &G) -> Spline {
+        //Work from the start of the route to the goal
+        let steps: Vec<Point> = self
+            .steps
+            .iter()
+            .rev()
+            .map(|step| (step.0 as f64, step.1 as f64))
+            .collect();
+        let last = self.steps.len().saturating_sub(1);
+        let controls = waypoints(map, &self.steps)
+            .into_iter()
+            .rev()
+            .map(|index| last - index)
+            .collect();
+
+        Spline::new(map, &steps, controls)
+    }
+
+    ///Checks the route can be followed on a map.
+    ///Every step must be passable, each move must be allowed by the rules' neighbourhood and the route
+    ///must start and end where the rules expect. The distance is measured in the same way as A*, with
+    ///each move weighted by the cost of the tile moved into, and must match the stored distance.
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// use std::path::Path;
+    /// use blitz_path::ValidationRules;
+    ///
+    /// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+    /// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+    /// let scene = &scenes[0];
+    ///
+    /// let mut rules = ValidationRules::default();
+    /// rules.start = Some(scene.start_pos);
+    /// rules.goal = Some(scene.goal_pos);
+    ///
+    /// if let Some(path) = blitz_path::a_star_path(&map, scene.start_pos, scene.goal_pos) {
+    ///     assert_eq!(path.validate(&map, &rules), Ok(()));
+    /// }
+    /// # Ok::<(), io::Error>(())
+    /// ```
+    pub fn validate<G: Grid>(&self, map: &G, rules: &ValidationRules) -> Result<(), RouteError> {
+        //An empty route is only correct when it starts at its goal
+        let (start, goal) = (self.steps.last().copied(), self.steps.first().copied());
+        let empty_ok = self.steps.is_empty() && rules.start == rules.goal;
+
+        if let Some(expected) = rules.start {
+            if start != Some(expected) && !empty_ok {
+                return Err(RouteError::WrongStart {
+                    expected,
+                    found: start,
+                });
+            }
+        }
+        if let Some(expected) = rules.goal {
+            if goal != Some(expected) && !empty_ok {
+                return Err(RouteError::WrongGoal {
+                    expected,
+                    found: goal,
+                });
+            }
+        }
+
+        for (index, step) in self.steps.iter().enumerate() {
+            if !map.is_passable(*step) {
+                return Err(RouteError::Impassable { index, step: *step });
+            }
+        }
+
+        //Measure the route from the start, moving into each step in turn
+        let mut distance = C::zero();
+        for index in (0..self.steps.len().saturating_sub(1)).rev() {
+            let (from, to) = (self.steps[index + 1], self.steps[index]);
+            if !is_legal(map, rules.neighbourhood, from, to) {
+                return Err(RouteError::IllegalMove { index, from, to });
+            }
+
+            let tile_cost = map.cost(to);
+            if !(tile_cost.is_finite() && tile_cost >= 0.0) {
+                return Err(RouteError::InvalidCost { index, step: to });
+            }
+            distance = distance.plus(straight::<C>(from, to).scale(tile_cost));
+        }
+
+        //Allow for rounding in each step of floating-point costs, as well as the rules' tolerance
+        let (expected, found) = (distance.to_f64(), self.distance.to_f64());
+        let difference = (expected - found).abs();
+        let tolerance = rules.tolerance.max(C::epsilon() * self.steps.len() as f64);
+        if difference.is_nan() || difference > tolerance * expected.abs().max(1.0) {
+            return Err(RouteError::WrongDistance { expected, found });
+        }
+
+        Ok(())
+    }
+}
+
+//Measures steps stored goal first, weighting each move by the cost of the tile moved into
+fn measure<C: Cost, G: Grid>(map: &G, steps: &[Coords2D]) -> C {
+    steps.windows(2).fold(C::zero(), |total, pair| {
+        total.plus(straight::<C>(pair[1], pair[0]).scale(map.cost(pair[0])))
+    })
+}
+
+//Cost of moving in a straight line between two tiles. Moves to a neighbouring tile cost the same as
+//in a search, while longer moves, such as those of a simplified route, are measured along the line
+//rather than as octile distances.
+fn straight<C: Cost>(from: Coords2D, to: Coords2D) -> C {
+    let (dx, dy) = (from.0.abs_diff(to.0), from.1.abs_diff(to.1));
+
+    if dx.max(dy) <= 1 {
+        C::distance(from, to)
+    } else {
+        C::distance((0, 0), (1, 0)).scale(distance(from, to))
+    }
+}
+
+//Whether a single move is allowed in a neighbourhood
+fn is_legal<G: Grid>(map: &G, neighbourhood: Neighbourhood, from: Coords2D, to: Coords2D) -> bool {
+    let dx = (to.0 as i64 - from.0 as i64).abs();
+    let dy = (to.1 as i64 - from.1 as i64).abs();
+
+    match neighbourhood {
+        Neighbourhood::Four => dx + dy == 1 && map.can_move(from, to),
+        Neighbourhood::Eight => {
+            dx.max(dy) == 1
+                && map.is_passable((to.0, from.1))
+                && map.is_passable((from.0, to.1))
+                && map.can_move(from, to)
+        }
+        Neighbourhood::EightCuttingCorners => dx.max(dy) == 1,
+        Neighbourhood::AnyAngle => from != to && line_of_sight(map, from, to),
+    }
+}
+
+//Indices of the steps which must be kept to move between them in straight lines
+fn waypoints<G: Grid>(map: &G, steps: &[Coords2D]) -> Vec<usize> {
+    let mut waypoints: Vec<usize> = Vec::with_capacity(steps.len());
+
+    for index in 0..steps.len() {
+        //Keep a step if the last waypoint can not see the step after it
+        let keep = match (waypoints.last(), steps.get(index + 1)) {
+            (Some(waypoint), Some(next)) => !line_of_sight(map, steps[*waypoint], *next),
+            _ => true,
+        };
+
+        if keep {
+            waypoints.push(index);
+        }
+    }
+
+    waypoints
+}
@@ -0,0 +1,170 @@
+use movingai::Coords2D;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+
+use crate::utils::centre;
+use crate::{distance, Point, Route, RouteError};
+
+///The times at which a route reaches each of its steps, created by [`Route::schedule`].
+///
+///Each move between two steps is split in half, with the first half taken at the speed of the tile
+///being left and the second at the speed of the tile being entered. Times start from zero at the start
+///of the route. Steps are listed from the goal to the start, in the same order as
+///[`Route::steps`](crate::Route::steps), so arrival `i` is step `i` of the route.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "ScheduleData"))]
+pub struct Schedule {
+    arrivals: Vec<(f64, Coords2D)>,
+    //Time at which the middle of each move is reached, with entry i being the move from arrival i + 1
+    //to arrival i
+    halfway: Vec<f64>,
+}
+
+//A Schedule as loaded, before checking it has a time for the middle of each move and its times are in order
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct ScheduleData {
+    arrivals: Vec<(f64, Coords2D)>,
+    halfway: Vec<f64>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<ScheduleData> for Schedule {
+    type Error = String;
+
+    fn try_from(data: ScheduleData) -> Result<Self, Self::Error> {
+        if data.halfway.len() != data.arrivals.len().saturating_sub(1) {
+            return Err(String::from("schedule needs a halfway time for each move"));
+        }
+
+        let ordered = data
+            .arrivals
+            .windows(2)
+            .zip(data.halfway.iter())
+            .all(|(pair, &half)| pair[1].0 <= half && half <= pair[0].0);
+        let finite = data.arrivals.iter().all(|arrival| arrival.0.is_finite());
+        if !ordered || !finite {
+            return Err(String::from("schedule times are not in order"));
+        }
+
+        Ok(Schedule {
+            arrivals: data.arrivals,
+            halfway: data.halfway,
+        })
+    }
+}
+
+impl Schedule {
+    ///Returns each step of the route with the time it is reached, from the goal to the start.
+    pub fn arrivals(&self) -> &[(f64, Coords2D)] {
+        &self.arrivals
+    }
+
+    ///Returns the time taken to reach the goal.
+    pub fn eta(&self) -> f64 {
+        self.arrivals.first().map_or(0.0, |arrival| arrival.0)
+    }
+
+    ///Returns the position reached at a time, or None if the route is empty.
+    ///Times before the start or after the goal is reached give the position at that end.
+    pub fn position_at(&self, time: f64) -> Option<Point> {
+        //Find the first arrival, counting from the goal, which is reached before the time
+        let last = self.arrivals.len().checked_sub(1)?;
+        let index = self
+            .arrivals
+            .partition_point(|arrival| arrival.0 >= time)
+            .max(1);
+        if index > last {
+            return Some(centre(self.arrivals[last].1));
+        }
+
+        let ((t0, a), (t1, b)) = (self.arrivals[index], self.arrivals[index - 1]);
+        let (a, b) = (centre(a), centre(b));
+        let middle = ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+        let half = self.halfway[index - 1];
+
+        Some(if time < half {
+            lerp(a, middle, fraction(time, t0, half))
+        } else {
+            lerp(middle, b, fraction(time, half, t1))
+        })
+    }
+}
+
+impl<C> Route<C> {
+    ///Returns the times at which the route reaches each step, moving at the speed given for each tile
+    ///in tiles per unit of time. Speeds must be greater than zero and finite.
+    ///
+    ///Speeds are often derived from a map's tile costs, so that moving through a tile takes as long as
+    ///its cost.
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// use std::path::Path;
+    /// use blitz_path::Grid;
+    ///
+    /// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+    /// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+    /// let scene = &scenes[0];
+    ///
+    /// if let Some(path) = blitz_path::a_star_path(&map, scene.start_pos, scene.goal_pos) {
+    ///     let schedule = path.schedule(|tile| 1.0 / map.cost(tile)).unwrap();
+    ///
+    ///     assert!((schedule.eta() - path.distance()).abs() < 1e-6);
+    ///     assert_eq!(schedule.position_at(schedule.eta()), Some((scene.goal_pos.0 as f64, scene.goal_pos.1 as f64)));
+    /// }
+    /// # Ok::<(), io::Error>(())
+    /// ```
+    pub fn schedule<F>(&self, mut speed: F) -> Result<Schedule, RouteError>
+    where
+        F: FnMut(Coords2D) -> f64,
+    {
+        let mut schedule = Schedule::default();
+        let mut previous: Option<(Coords2D, f64, f64)> = None;
+        let last = self.len().saturating_sub(1);
+
+        for (index, &step) in self.iter_forward().enumerate() {
+            let current = speed(step);
+            if !(current.is_finite() && current > 0.0) {
+                return Err(RouteError::InvalidSpeed {
+                    index: last - index,
+                    step,
+                });
+            }
+
+            let time = match previous {
+                Some((from, before, time)) => {
+                    let half = distance(from, step) / 2.0;
+                    let time = time + half / before;
+                    schedule.halfway.push(time);
+                    time + half / current
+                }
+                None => 0.0,
+            };
+
+            schedule.arrivals.push((time, step));
+            previous = Some((step, current, time));
+        }
+
+        //Times are found from the start, but listed from the goal like the route's steps
+        schedule.arrivals.reverse();
+        schedule.halfway.reverse();
+        Ok(schedule)
+    }
+}
+
+fn fraction(time: f64, from: f64, to: f64) -> f64 {
+    if to > from {
+        ((time - from) / (to - from)).clamp(0.0, 1.0)
+    } else {
+        1.0
+    }
+}
+
+fn lerp(a: Point, b: Point, fraction: f64) -> Point {
+    (a.0 + (b.0 - a.0) * fraction, a.1 + (b.1 - a.1) * fraction)
+}
@@ -0,0 +1,282 @@
+use movingai::Coords2D;
+
+use crate::{Grid, Point};
+
+//Distance within which points are treated as lying on a grid line
+const EPSILON: f64 = 1e-9;
+
+///Whether there is a clear straight line between the centres of two tiles.
+///
+///Every tile the line passes through, including the two end tiles, must be passable. Where the line
+///passes exactly through the corner of a tile, both tiles either side of the corner must be passable,
+///just as a route may only move diagonally when both straight steps making up the diagonal are passable.
+/// # Examples
+///
+/// ```
+/// use blitz_path::BitGrid;
+///
+/// let mut grid = BitGrid::new(3, 3);
+/// for tile in [(0, 0), (1, 0), (2, 0), (0, 1), (0, 2), (1, 2), (2, 2)].iter() {
+///     grid.set_passable(*tile, true);
+/// }
+///
+/// assert!(blitz_path::line_of_sight(&grid, (0, 0), (2, 0)));
+/// assert!(blitz_path::line_of_sight(&grid, (0, 2), (0, 0)));
+/// //The line between these tiles passes the corner of the impassable tiles in the middle
+/// assert!(!blitz_path::line_of_sight(&grid, (1, 0), (0, 1)));
+/// ```
+pub fn line_of_sight<G: Grid>(map: &G, a: Coords2D, b: Coords2D) -> bool {
+    if !map.is_passable(a) {
+        return false;
+    }
+
+    let (nx, ny) = (
+        (b.0 as i64 - a.0 as i64).abs(),
+        (b.1 as i64 - a.1 as i64).abs(),
+    );
+    let (sx, sy) = (
+        (b.0 as i64 - a.0 as i64).signum(),
+        (b.1 as i64 - a.1 as i64).signum(),
+    );
+    let (mut x, mut y) = (a.0 as i64, a.1 as i64);
+    let (mut ix, mut iy) = (0, 0);
+
+    //Walk through every tile the line touches, choosing whether the next tile edge it crosses is
+    //vertical, horizontal or exactly a corner
+    while ix < nx || iy < ny {
+        let decision = (1 + 2 * ix) * ny - (1 + 2 * iy) * nx;
+
+        if decision == 0 {
+            if !(tile_passable(map, x + sx, y) && tile_passable(map, x, y + sy)) {
+                return false;
+            }
+            x += sx;
+            y += sy;
+            ix += 1;
+            iy += 1;
+        } else if decision < 0 {
+            x += sx;
+            ix += 1;
+        } else {
+            y += sy;
+            iy += 1;
+        }
+
+        if !tile_passable(map, x, y) {
+            return false;
+        }
+    }
+
+    true
+}
+
+///Casts a ray from the centre of `origin` in a `direction`, returning the last tile it reaches before
+///being stopped by an impassable tile or the edge of the map.
+///
+///The ray follows the same rules as [`line_of_sight`], so stops rather than passing exactly through the
+///corner of an impassable tile. Returns None if `origin` itself is impassable, or `origin` if the
+///direction has no length.
+/// # Examples
+///
+/// ```
+/// use blitz_path::BitGrid;
+///
+/// let mut grid = BitGrid::new(5, 5);
+/// for x in 0..4 {
+///     grid.set_passable((x, 2), true);
+/// }
+///
+/// assert_eq!(blitz_path::raycast(&grid, (0, 2), (1.0, 0.0)), Some((3, 2)));
+/// assert_eq!(blitz_path::raycast(&grid, (0, 2), (1.0, 0.1)), Some((3, 2)));
+/// assert_eq!(blitz_path::raycast(&grid, (0, 2), (0.0, 1.0)), Some((0, 2)));
+/// assert_eq!(blitz_path::raycast(&grid, (4, 2), (1.0, 0.0)), None);
+/// ```
+pub fn raycast<G: Grid>(map: &G, origin: Coords2D, direction: (f64, f64)) -> Option<Coords2D> {
+    if !map.is_passable(origin) {
+        return None;
+    }
+
+    let (dx, dy) = direction;
+    if !(dx.is_finite() && dy.is_finite()) || (dx == 0.0 && dy == 0.0) {
+        return Some(origin);
+    }
+
+    let (sx, sy) = (dx.signum() as i64, dy.signum() as i64);
+    let (mut x, mut y) = (origin.0 as i64, origin.1 as i64);
+
+    //How far along the ray the next vertical and horizontal tile edges are, and the distance between them
+    let delta = |d: f64| {
+        if d == 0.0 {
+            f64::INFINITY
+        } else {
+            1.0 / d.abs()
+        }
+    };
+    let (delta_x, delta_y) = (delta(dx), delta(dy));
+    let (mut next_x, mut next_y) = (delta_x / 2.0, delta_y / 2.0);
+
+    loop {
+        let (step_x, step_y) = if (next_x - next_y).abs() <= EPSILON * next_x.min(next_y) {
+            (sx, sy)
+        } else if next_x < next_y {
+            (sx, 0)
+        } else {
+            (0, sy)
+        };
+
+        //Passing exactly through a corner needs both tiles either side of it to be passable
+        let corner_clear = step_x == 0
+            || step_y == 0
+            || (tile_passable(map, x + step_x, y) && tile_passable(map, x, y + step_y));
+        if !corner_clear || !tile_passable(map, x + step_x, y + step_y) {
+            return Some((x as usize, y as usize));
+        }
+
+        x += step_x;
+        y += step_y;
+        if step_x != 0 {
+            next_x += delta_x;
+        }
+        if step_y != 0 {
+            next_y += delta_y;
+        }
+    }
+}
+
+fn tile_passable<G: Grid>(map: &G, x: i64, y: i64) -> bool {
+    x >= 0 && y >= 0 && map.is_passable((x as usize, y as usize))
+}
+
+//Whether a straight line between two points stays clear of impassable tiles. The line may run along
+//the edge of an impassable tile, but may only pass diagonally through a corner when both tiles either
+//side of it are passable, the same rule used by line_of_sight.
+pub fn segment_clear<G: Grid>(grid: &G, a: Point, b: Point) -> bool {
+    //Move the origin to the corner of tile (0, 0), so tile edges lie on whole numbers
+    let (ax, ay) = (a.0 + 0.5, a.1 + 0.5);
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let at = |t: f64| (ax + dx * t, ay + dy * t);
+
+    let mut x_lines = Crossings::new(ax, dx).peekable();
+    let mut y_lines = Crossings::new(ay, dy).peekable();
+    let mut previous = 0.0;
+
+    loop {
+        //Find the next point where the line crosses a tile edge
+        let (t, corner) = match (x_lines.peek().copied(), y_lines.peek().copied()) {
+            (Some(x), Some(y)) if (x - y).abs() < EPSILON => {
+                x_lines.next();
+                y_lines.next();
+                (x, true)
+            }
+            (Some(x), Some(y)) if x < y => (x_lines.next().unwrap(), false),
+            (_, Some(_)) => (y_lines.next().unwrap(), false),
+            (Some(_), None) => (x_lines.next().unwrap(), false),
+            (None, None) => (1.0, false),
+        };
+
+        //Check the tile the line passes through since the last crossing
+        if t - previous > EPSILON {
+            let (x, y) = at((previous + t) / 2.0);
+            if !point_clear(grid, x, y) {
+                return false;
+            }
+        }
+
+        if t >= 1.0 {
+            return true;
+        }
+
+        //Lines passing through the corner of a tile must not squeeze between impassable tiles
+        let (x, y) = at(t);
+        let on_corner = corner || (is_whole(x) && is_whole(y));
+        if on_corner && !corner_clear(grid, x.round(), y.round(), dx, dy) {
+            return false;
+        }
+
+        previous = t;
+    }
+}
+
+//Whether a point, in coordinates where tile edges lie on whole numbers, is in a passable tile or on
+//the edge of one
+fn point_clear<G: Grid>(grid: &G, x: f64, y: f64) -> bool {
+    match (is_whole(x), is_whole(y)) {
+        (false, false) => passable(grid, x.floor(), y.floor()),
+        (true, false) => {
+            passable(grid, x.round() - 1.0, y.floor()) || passable(grid, x.round(), y.floor())
+        }
+        (false, true) => {
+            passable(grid, x.floor(), y.round() - 1.0) || passable(grid, x.floor(), y.round())
+        }
+        (true, true) => true,
+    }
+}
+
+//Whether a line in direction (dx, dy) can pass through the corner (x, y) shared by four tiles
+fn corner_clear<G: Grid>(grid: &G, x: f64, y: f64, dx: f64, dy: f64) -> bool {
+    let quadrant = |qx: f64, qy: f64| passable(grid, x + (qx - 1.0) / 2.0, y + (qy - 1.0) / 2.0);
+    let (sx, sy) = (dx.signum(), dy.signum());
+
+    if dx.abs() < EPSILON {
+        //Moving along a vertical edge, one side must be passable before and after the corner
+        (quadrant(-1.0, -1.0) && quadrant(-1.0, 1.0)) || (quadrant(1.0, -1.0) && quadrant(1.0, 1.0))
+    } else if dy.abs() < EPSILON {
+        (quadrant(-1.0, -1.0) && quadrant(1.0, -1.0)) || (quadrant(-1.0, 1.0) && quadrant(1.0, 1.0))
+    } else {
+        //Moving diagonally, both tiles either side of the line must be passable, as in line_of_sight
+        quadrant(sx, -sy) && quadrant(-sx, sy)
+    }
+}
+
+//Whether the tile whose top left corner is (x, y) is passable
+fn passable<G: Grid>(grid: &G, x: f64, y: f64) -> bool {
+    x >= 0.0 && y >= 0.0 && grid.is_passable((x as usize, y as usize))
+}
+
+fn is_whole(value: f64) -> bool {
+    (value - value.round()).abs() < EPSILON
+}
+
+//Fractions of the way along a line at which it crosses each whole number, from start to end
+struct Crossings {
+    start: f64,
+    delta: f64,
+    next: f64,
+    step: f64,
+}
+
+impl Crossings {
+    fn new(start: f64, delta: f64) -> Crossings {
+        let step = delta.signum();
+        let next = if delta > 0.0 {
+            (start + EPSILON).floor() + 1.0
+        } else {
+            (start - EPSILON).ceil() - 1.0
+        };
+
+        Crossings {
+            start,
+            delta,
+            next,
+            step,
+        }
+    }
+}
+
+impl Iterator for Crossings {
+    type Item = f64;
+
+    fn next(&mut self) -> Option<f64> {
+        if self.delta.abs() < EPSILON {
+            return None;
+        }
+
+        let t = (self.next - self.start) / self.delta;
+        if t < 1.0 - EPSILON {
+            self.next += self.step;
+            Some(t)
+        } else {
+            None
+        }
+    }
+}
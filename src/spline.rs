@@ -0,0 +1,213 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+
+use crate::sight::segment_clear;
+use crate::utils::length;
+use crate::{Grid, Point};
+
+//Number of points each section of the curve is split into per tile of its length
+const SAMPLES_PER_TILE: f64 = 4.0;
+
+///A smooth curve through a route, created by [`Route::smooth`](crate::Route::smooth).
+///
+///The curve is a centripetal Catmull-Rom spline, which passes through each of its control points
+///without forming loops or cusps. Sections which would cross an impassable tile are replaced by
+///straight lines. Points along the curve are found by their distance from the start, measured along
+///the curve.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "SplineData"))]
+pub struct Spline {
+    controls: Vec<Point>,
+    //Points along the curve, with the distance along the curve to each
+    samples: Vec<(f64, Point)>,
+}
+
+//A Spline as loaded, before checking the distance to each sample is measured along the curve
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct SplineData {
+    controls: Vec<Point>,
+    samples: Vec<(f64, Point)>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SplineData> for Spline {
+    type Error = String;
+
+    fn try_from(data: SplineData) -> Result<Self, Self::Error> {
+        let finite = |point: &Point| point.0.is_finite() && point.1.is_finite();
+        if !data.controls.iter().all(finite) || !data.samples.iter().all(|x| finite(&x.1)) {
+            return Err(String::from("spline point is not finite"));
+        }
+        if data.controls.is_empty() != data.samples.is_empty() {
+            return Err(String::from("spline needs a control point for its samples"));
+        }
+
+        //Distances must start from zero and grow by the length between each pair of samples
+        let start = data.samples.first().map_or(0.0, |sample| sample.0);
+        let measured = data.samples.windows(2).all(|pair| {
+            let ((d0, a), (d1, b)) = (pair[0], pair[1]);
+            (d1 - d0 - length(a, b)).abs() <= 1e-9 * d1.abs().max(1.0)
+        });
+        if start != 0.0 || !measured {
+            return Err(String::from(
+                "spline distances are not measured along the curve",
+            ));
+        }
+
+        Ok(Spline {
+            controls: data.controls,
+            samples: data.samples,
+        })
+    }
+}
+
+impl Spline {
+    //Creates a spline through some of the steps of a route, adding more steps wherever it is blocked
+    pub(crate) fn new<G: Grid>(map: &G, steps: &[Point], mut controls: Vec<usize>) -> Spline {
+        let mut straight = vec![false; controls.len()];
+        let mut section = 0;
+
+        while section + 1 < controls.len() {
+            let points = curve(steps, &controls, &straight, section);
+            let clear = points
+                .windows(2)
+                .all(|pair| segment_clear(map, pair[0], pair[1]));
+
+            if clear {
+                section += 1;
+            } else if controls[section + 1] - controls[section] > 1 {
+                //Add the step half way between the control points and check the section again,
+                //along with the one before it, which the new point also changes
+                let middle = (controls[section] + controls[section + 1]) / 2;
+                controls.insert(section + 1, middle);
+                straight.insert(section + 1, false);
+                section = section.saturating_sub(1);
+            } else {
+                straight[section] = true;
+                section += 1;
+            }
+        }
+
+        //Record the distance along the curve to each point
+        let mut samples = Vec::new();
+        let mut distance = 0.0;
+        for section in 0..controls.len().saturating_sub(1) {
+            let points = curve(steps, &controls, &straight, section);
+            let skip = if section == 0 { 0 } else { 1 };
+
+            for point in points.into_iter().skip(skip) {
+                if let Some(&(_, last)) = samples.last() {
+                    distance += length(last, point);
+                }
+                samples.push((distance, point));
+            }
+        }
+        if let (true, Some(&index)) = (samples.is_empty(), controls.first()) {
+            samples.push((0.0, steps[index]));
+        }
+
+        Spline {
+            controls: controls.into_iter().map(|index| steps[index]).collect(),
+            samples,
+        }
+    }
+
+    ///Returns the points the curve passes through, from the start to the goal.
+    pub fn controls(&self) -> &[Point] {
+        &self.controls
+    }
+
+    ///Returns the length of the curve.
+    pub fn length(&self) -> f64 {
+        self.samples.last().map_or(0.0, |sample| sample.0)
+    }
+
+    ///Returns the point a distance along the curve from its start, or None if the curve is empty.
+    ///Distances beyond either end of the curve give the point at that end.
+    pub fn point_at(&self, distance: f64) -> Option<Point> {
+        let index = self
+            .samples
+            .partition_point(|sample| sample.0 < distance)
+            .min(self.samples.len().checked_sub(1)?);
+        if index == 0 {
+            return Some(self.samples[0].1);
+        }
+
+        let ((d0, a), (d1, b)) = (self.samples[index - 1], self.samples[index]);
+        let fraction = if d1 > d0 {
+            (distance - d0) / (d1 - d0)
+        } else {
+            1.0
+        };
+        let fraction = fraction.clamp(0.0, 1.0);
+
+        Some((a.0 + (b.0 - a.0) * fraction, a.1 + (b.1 - a.1) * fraction))
+    }
+
+    ///Returns points spaced evenly along the curve, from its start to its goal. The final gap may be
+    ///shorter than `spacing`, which must be greater than zero.
+    pub fn sample(&self, spacing: f64) -> Vec<Point> {
+        assert!(spacing > 0.0, "spacing must be greater than zero");
+
+        let length = self.length();
+        let count = (length / spacing).ceil() as usize;
+        let mut points: Vec<Point> = (0..count)
+            .filter_map(|index| self.point_at(index as f64 * spacing))
+            .collect();
+        points.extend(self.samples.last().map(|sample| sample.1));
+
+        points
+    }
+}
+
+//Points along a section of the curve between two control points, including both ends
+fn curve(steps: &[Point], controls: &[usize], straight: &[bool], section: usize) -> Vec<Point> {
+    let (p1, p2) = (steps[controls[section]], steps[controls[section + 1]]);
+    let count = ((length(p1, p2) * SAMPLES_PER_TILE).ceil() as usize).max(8);
+
+    if straight[section] {
+        return (0..=count)
+            .map(|index| {
+                let t = index as f64 / count as f64;
+                (p1.0 + (p2.0 - p1.0) * t, p1.1 + (p2.1 - p1.1) * t)
+            })
+            .collect();
+    }
+
+    //Ends of the curve continue in a straight line beyond the first and last control points
+    let p0 = match section {
+        0 => (2.0 * p1.0 - p2.0, 2.0 * p1.1 - p2.1),
+        _ => steps[controls[section - 1]],
+    };
+    let p3 = match controls.get(section + 2) {
+        Some(index) => steps[*index],
+        None => (2.0 * p2.0 - p1.0, 2.0 * p2.1 - p1.1),
+    };
+
+    //Centripetal parameterisation, spacing knots by the square root of the distance between points
+    let t1 = length(p0, p1).sqrt();
+    let t2 = t1 + length(p1, p2).sqrt();
+    let t3 = t2 + length(p2, p3).sqrt();
+
+    (0..=count)
+        .map(|index| {
+            let t = t1 + (t2 - t1) * index as f64 / count as f64;
+            let a1 = lerp(p0, p1, 0.0, t1, t);
+            let a2 = lerp(p1, p2, t1, t2, t);
+            let a3 = lerp(p2, p3, t2, t3, t);
+            let b1 = lerp(a1, a2, 0.0, t2, t);
+            let b2 = lerp(a2, a3, t1, t3, t);
+            lerp(b1, b2, t1, t2, t)
+        })
+        .collect()
+}
+
+//Interpolates between two points at knots ta and tb
+fn lerp(a: Point, b: Point, ta: f64, tb: f64, t: f64) -> Point {
+    let fraction = (t - ta) / (tb - ta);
+    (a.0 + (b.0 - a.0) * fraction, a.1 + (b.1 - a.1) * fraction)
+}
@@ -0,0 +1,26 @@
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+///Measures of the work done by a search, returned by
+///[`a_star_path_with_stats`](crate::a_star_path_with_stats) and
+///[`jps_path_with_stats`](crate::jps_path_with_stats).
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SearchStats {
+    ///Nodes expanded, including the start but not the goal.
+    pub expanded: usize,
+    ///Nodes pushed to the open list, including the start. JPS expands the start straight away rather
+    ///than pushing it, but counts it in the same way as A*.
+    pub generated: usize,
+    ///The largest number of nodes on the open list at once, including any which are out of date.
+    pub max_open: usize,
+    ///Nodes which were found by a shorter route after being expanded, so had to be expanded again.
+    ///JPS never reopens nodes, so this is always zero for JPS.
+    pub reopened: usize,
+    ///Nodes found by jumping from an expanded node. This is always zero for A*.
+    pub jump_points: usize,
+    ///The time taken by the search.
+    pub elapsed: Duration,
+}
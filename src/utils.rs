@@ -1,15 +1,32 @@
 use movingai::Coords2D;
 
 use crate::node::Node;
-
+use crate::Point;
+
+///Returns the straight-line distance between the centres of two tiles.
+/// # Examples
+///
+/// ```
+/// assert_eq!(blitz_path::distance((0, 0), (3, 4)), 5.0);
+/// ```
 pub fn distance(a: Coords2D, b: Coords2D) -> f64 {
     let (x, y) = (a.0 as f64, a.1 as f64);
     let (p, q) = (b.0 as f64, b.1 as f64);
     ((x - p) * (x - p) + (y - q) * (y - q)).sqrt()
 }
 
+//Straight-line distance between two points
+pub fn length(a: Point, b: Point) -> f64 {
+    ((a.0 - b.0) * (a.0 - b.0) + (a.1 - b.1) * (a.1 - b.1)).sqrt()
+}
+
+//The point at the centre of a tile
+pub fn centre(tile: Coords2D) -> Point {
+    (tile.0 as f64, tile.1 as f64)
+}
+
 //Helper function to recreate path once goal is located
-pub fn rewind_jps(start: &Node, closed: &Vec<Node>) -> Vec<Coords2D> {
+pub fn rewind_jps<C>(start: &Node<Coords2D, C>, closed: &[Node<Coords2D, C>]) -> Vec<Coords2D> {
     let mut path = Vec::with_capacity(closed.len().pow(2) + 1);
 
     path.push(start.position);
@@ -39,26 +56,6 @@ pub fn rewind_jps(start: &Node, closed: &Vec<Node>) -> Vec<Coords2D> {
     path
 }
 
-//Helper function to recreate path once goal is located
-pub fn rewind(start: &Node, closed: &Vec<Node>) -> Vec<Coords2D> {
-    let mut path = Vec::with_capacity(closed.len() + 1);
-
-    path.push(start.position);
-
-    let mut parent = start.parent;
-    let mut node = start.position;
-
-    while parent != node {
-        if let Some(step) = closed.iter().find(|x| x.position == parent) {
-            parent = step.parent;
-            node = step.position;
-            path.push(node);
-        }
-    }
-
-    path
-}
-
 pub fn direction(current: Coords2D, parent: Coords2D) -> (i32, i32) {
     (
         current.0.cmp(&parent.0) as i32,
@@ -0,0 +1,247 @@
+use movingai::Coords2D;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+
+use crate::sight::segment_clear;
+use crate::utils::length;
+use crate::{BitGrid, Grid, PathError, Point, Route, SearchOptions};
+
+///A graph joining each convex corner of the impassable tiles in a grid to every other corner it can see.
+///
+///Shortest routes across open space only ever turn at the corners of obstacles, so searching the graph
+///gives the shortest route moving at any angle, rather than only in the eight directions of the grid.
+///Each tile (x, y) covers the square from (x - 0.5, y - 0.5) to (x + 0.5, y + 0.5), so corners lie
+///half way between tiles. Routes may run along the edge of an impassable tile, but can not pass
+///between two impassable tiles which only touch at a corner.
+///
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use std::path::Path;
+/// use blitz_path::VisibilityGraph;
+///
+/// let map = movingai::parser::parse_map_file(Path::new("./tests/map/maze512-32-9.map"))?;
+/// let scenes = movingai::parser::parse_scen_file(Path::new("./tests/map/maze512-32-9.map.scen"))?;
+/// let scene = &scenes[0];
+///
+/// let graph = VisibilityGraph::from_grid(&map);
+/// let route = blitz_path::visibility_path(&graph, scene.start_pos, scene.goal_pos);
+///
+/// if let Some(route) = route {
+///     assert!(route.distance() <= scene.optimal_length + 1e-6);
+/// }
+/// # Ok::<(), io::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "VisibilityGraphData"))]
+pub struct VisibilityGraph {
+    grid: BitGrid,
+    corners: Vec<Corner>,
+    edges: Vec<Vec<(usize, f64)>>,
+}
+
+//A VisibilityGraph as loaded, before checking its edges join corners which exist
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct VisibilityGraphData {
+    grid: BitGrid,
+    corners: Vec<Corner>,
+    edges: Vec<Vec<(usize, f64)>>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<VisibilityGraphData> for VisibilityGraph {
+    type Error = String;
+
+    fn try_from(data: VisibilityGraphData) -> Result<Self, Self::Error> {
+        if data.edges.len() != data.corners.len() {
+            return Err(String::from(
+                "visibility graph needs a list of edges for each corner",
+            ));
+        }
+
+        let finite = |corner: &Corner| corner.point.0.is_finite() && corner.point.1.is_finite();
+        if !data.corners.iter().all(finite) {
+            return Err(String::from("visibility graph corner is not finite"));
+        }
+
+        let valid = |edge: &(usize, f64)| {
+            edge.0 < data.corners.len() && edge.1.is_finite() && edge.1 >= 0.0
+        };
+        if !data.edges.iter().flatten().all(valid) {
+            return Err(String::from("visibility graph edge is not valid"));
+        }
+
+        Ok(VisibilityGraph {
+            grid: data.grid,
+            corners: data.corners,
+            edges: data.edges,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Corner {
+    point: Point,
+    //Direction from the corner to the impassable tile it belongs to
+    blocked: (f64, f64),
+}
+
+impl Corner {
+    //Whether a line through the corner towards a point only touches the impassable tile, which is the
+    //only way a shortest route can turn at the corner
+    fn is_tangent(&self, point: Point) -> bool {
+        let (dx, dy) = (point.0 - self.point.0, point.1 - self.point.1);
+        dx * dy * self.blocked.0 * self.blocked.1 <= 0.0
+    }
+}
+
+impl VisibilityGraph {
+    ///Creates a visibility graph of the convex corners of the impassable tiles in a grid.
+    ///Tiles outside the grid count as impassable.
+    pub fn from_grid<G: Grid>(grid: &G) -> VisibilityGraph {
+        let grid = BitGrid::from_grid(grid);
+        let (width, height) = (grid.width(), grid.height());
+
+        //A corner is convex when exactly one of the four tiles around it is impassable
+        let mut corners = Vec::new();
+        for y in 0..=height {
+            for x in 0..=width {
+                let around = [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)];
+                let blocked: Vec<(f64, f64)> = around
+                    .iter()
+                    .copied()
+                    .filter(|(dx, dy)| {
+                        let tile = (
+                            (x as f64 + (dx - 1.0) / 2.0) as isize as usize,
+                            (y as f64 + (dy - 1.0) / 2.0) as isize as usize,
+                        );
+                        !grid.is_passable(tile)
+                    })
+                    .collect();
+
+                if let [blocked] = blocked[..] {
+                    corners.push(Corner {
+                        point: (x as f64 - 0.5, y as f64 - 0.5),
+                        blocked,
+                    });
+                }
+            }
+        }
+
+        //Join corners which can see each other, if a shortest route could turn at both
+        let mut edges = vec![Vec::new(); corners.len()];
+        for (index, corner) in corners.iter().enumerate() {
+            for (other_index, other) in corners.iter().enumerate().skip(index + 1) {
+                if corner.is_tangent(other.point)
+                    && other.is_tangent(corner.point)
+                    && segment_clear(&grid, corner.point, other.point)
+                {
+                    let distance = length(corner.point, other.point);
+                    edges[index].push((other_index, distance));
+                    edges[other_index].push((index, distance));
+                }
+            }
+        }
+
+        VisibilityGraph {
+            grid,
+            corners,
+            edges,
+        }
+    }
+
+    ///Returns the position of each corner in the graph.
+    pub fn corners(&self) -> Vec<Point> {
+        self.corners.iter().map(|corner| corner.point).collect()
+    }
+
+    //Corners visible from a point, which a shortest route from the point could turn at
+    fn visible(&self, point: Point) -> Vec<(usize, f64)> {
+        self.corners
+            .iter()
+            .enumerate()
+            .filter(|(_, corner)| {
+                corner.is_tangent(point) && segment_clear(&self.grid, point, corner.point)
+            })
+            .map(|(index, corner)| (index, length(point, corner.point)))
+            .collect()
+    }
+}
+
+///Creates a new route using a visibility graph.
+///Returns a Route struct containing the distance to the goal and each point where the route turns,
+///which is the shortest route between the centres of the two tiles moving at any angle.
+///
+///See [`VisibilityGraph`] for an example.
+pub fn visibility_path(
+    graph: &VisibilityGraph,
+    start: Coords2D,
+    goal: Coords2D,
+) -> Option<Route<f64, Point>> {
+    visibility_path_with(graph, start, goal, &SearchOptions::default()).ok()
+}
+
+///Creates a new route using a visibility graph, configured by the given SearchOptions.
+///Returns a PathError if there is no route to the goal.
+pub fn visibility_path_with(
+    graph: &VisibilityGraph,
+    start: Coords2D,
+    goal: Coords2D,
+    options: &SearchOptions,
+) -> Result<Route<f64, Point>, PathError> {
+    if start == goal {
+        return Ok(Route::from((0.0, vec![])));
+    }
+    if !graph.grid.is_passable(start) || !graph.grid.is_passable(goal) {
+        return Err(PathError::NoPath);
+    }
+
+    let start = (start.0 as f64, start.1 as f64);
+    let goal = (goal.0 as f64, goal.1 as f64);
+
+    //Go straight to the goal when nothing is in the way
+    if segment_clear(&graph.grid, start, goal) {
+        return Ok(Route::from((length(start, goal), vec![goal, start])));
+    }
+
+    //The start and goal are added to the graph after the corners
+    let (start_index, goal_index) = (graph.corners.len(), graph.corners.len() + 1);
+    let point = |index: usize| match index {
+        index if index == start_index => start,
+        index if index == goal_index => goal,
+        index => graph.corners[index].point,
+    };
+
+    let from_start = graph.visible(start);
+    let mut to_goal = vec![None; graph.corners.len()];
+    for (index, distance) in graph.visible(goal) {
+        to_goal[index] = Some(distance);
+    }
+
+    let route: Route<f64, usize> = crate::a_star(
+        start_index,
+        goal_index,
+        |index| {
+            let edges = match index {
+                index if index == start_index => &from_start,
+                index => &graph.edges[index],
+            };
+            let finish = to_goal.get(index).copied().flatten();
+            edges
+                .iter()
+                .copied()
+                .chain(finish.map(|distance| (goal_index, distance)))
+        },
+        |index| length(point(index), goal),
+        options,
+    )?;
+
+    let steps = route.steps().into_iter().map(point).collect();
+    Ok(Route::from((route.distance(), steps)))
+}
@@ -6,7 +6,18 @@ mod tests {
     use movingai::parser::parse_scen_file;
     use movingai::{MovingAiMap, SceneRecord};
 
-    use blitz_path::{a_star_path, jps_path};
+    use blitz_path::{a_star_path, a_star_path_fixed, jps_path, jps_path_fixed};
+    #[cfg(feature = "observer")]
+    use blitz_path::{a_star_path_observed, jps_path_observed, SearchObserver};
+    use blitz_path::{a_star_path_with, jps_path_with, Cost, Fixed, PathError, Route};
+    use blitz_path::{a_star_path_with_stats, jps_path_with_stats, SearchStats};
+    use blitz_path::{distance, BitGrid, Coords2D, Grid, SearchOptions};
+    use blitz_path::{find_paths, find_paths_with, SearchContext};
+    use blitz_path::{navmesh_path, navmesh_path_with, NavMesh};
+    use blitz_path::{render_ascii, render_ppm, Overlay};
+    use blitz_path::{visibility_path, visibility_path_with, VisibilityGraph};
+    use blitz_path::{CompactRoute, Neighbourhood, RouteError, Segment, ValidationRules};
+    use blitz_path::{FollowStatus, PathFollower, Schedule};
 
     const MAP: &str = "./tests/map/maze512-32-9.map";
     const SCEN: &str = "./tests/map/maze512-32-9.map.scen";
@@ -15,62 +26,112 @@ mod tests {
 
     enum Algorithm {
         AStar,
-        JPS,
+        AStarFixed,
+        Jps,
+        JpsFixed,
     }
 
     fn test_scen(
         algorithm: Algorithm,
         map: &MovingAiMap,
-        scen: &Vec<SceneRecord>,
+        scen: &[SceneRecord],
         tests: Vec<usize>,
     ) -> Vec<String> {
         let mut errors = Vec::new();
 
         for index in tests {
             let scene = &scen[index];
-            let path = match algorithm {
-                Algorithm::AStar => a_star_path(map, scene.start_pos, scene.goal_pos),
-                Algorithm::JPS => jps_path(map, scene.start_pos, scene.goal_pos),
+            let (start, goal) = (scene.start_pos, scene.goal_pos);
+            //JPS can move diagonally past the corners of impassable tiles
+            let neighbourhood = match algorithm {
+                Algorithm::AStar | Algorithm::AStarFixed => Neighbourhood::Eight,
+                Algorithm::Jps | Algorithm::JpsFixed => Neighbourhood::EightCuttingCorners,
             };
+            let mut rules = ValidationRules::default();
+            rules.neighbourhood = neighbourhood;
+            rules.start = Some(start);
+            rules.goal = Some(goal);
+            let result =
+                match algorithm {
+                    Algorithm::AStar => a_star_path(map, start, goal)
+                        .map(|x| (x.validate(map, &rules), x.into_steps())),
+                    Algorithm::AStarFixed => a_star_path_fixed(map, start, goal)
+                        .map(|x| (x.validate(map, &rules), x.into_steps())),
+                    Algorithm::Jps => jps_path(map, start, goal)
+                        .map(|x| (x.validate(map, &rules), x.into_steps())),
+                    Algorithm::JpsFixed => jps_path_fixed(map, start, goal)
+                        .map(|x| (x.validate(map, &rules), x.into_steps())),
+                };
 
-            match path {
+            match result {
                 None => {
-                    errors.push(format!("Test #{} found no path", index));
+                    //Show the areas around the start and goal, which may be far apart
+                    let around = |tile: Coords2D| Overlay {
+                        start: Some(start),
+                        goal: Some(goal),
+                        area: Some((
+                            (tile.0.saturating_sub(2), tile.1.saturating_sub(2)),
+                            (tile.0 + 2, tile.1 + 2),
+                        )),
+                        ..Overlay::default()
+                    };
+                    errors.push(format!(
+                        "Test #{} found no path\n{}\n{}",
+                        index,
+                        render_ascii(map, &around(start)),
+                        render_ascii(map, &around(goal))
+                    ));
                 }
-                Some(path) => {
-                    for num in 0..path.steps().len() - 1 {
-                        //Test wheter the x or y diustance between any adjacent steps is more than 1
-                        let direction_x =
-                            path.steps()[num + 1].0 as i32 - path.steps()[num].0 as i32;
-                        let direction_y =
-                            path.steps()[num + 1].1 as i32 - path.steps()[num].1 as i32;
-                        if direction_x < -1
-                            || direction_x > 1
-                            || direction_y < -1
-                            || direction_y > 1
-                        {
-                            errors.push(format!("Test #{} did not unwind correctly", index))
-                        }
-                    }
+                Some((Err(error), route)) => {
+                    errors.push(format!(
+                        "Test #{} is not valid: {}\n{}",
+                        index,
+                        error,
+                        render(map, route)
+                    ));
                 }
+                Some((Ok(()), _)) => {}
             }
         }
 
         errors
     }
 
+    //Creates a grid where every tile is passable except those blocked
+    fn open_grid(width: usize, height: usize, blocked: &[Coords2D]) -> BitGrid {
+        let mut grid = BitGrid::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                grid.set_passable((x, y), !blocked.contains(&(x, y)));
+            }
+        }
+
+        grid
+    }
+
+    //Draws the area around a route, to show why a test failed
+    fn render(map: &MovingAiMap, route: Vec<Coords2D>) -> String {
+        let mut overlay = Overlay {
+            route,
+            ..Overlay::default()
+        };
+        overlay.area = overlay.bounds(2);
+
+        render_ascii(map, &overlay)
+    }
+
     #[test]
     fn jps() {
         let map = parse_map_file(Path::new(MAP)).unwrap();
         let scenes = parse_scen_file(Path::new(SCEN)).unwrap();
         let tests = TEST_NUMS_JPS.to_vec();
 
-        let errors = test_scen(Algorithm::JPS, &map, &scenes, tests);
+        let errors = test_scen(Algorithm::Jps, &map, &scenes, tests);
 
         assert!(
-            errors.len() < 1,
-            "The following tests failed:\n{:?}",
-            errors
+            errors.is_empty(),
+            "The following tests failed:\n{}",
+            errors.join("\n")
         );
     }
 
@@ -81,12 +142,12 @@ mod tests {
         let scenes = parse_scen_file(Path::new(SCEN)).unwrap();
         let all_tests = (0..scenes.len()).collect();
 
-        let errors = test_scen(Algorithm::JPS, &map, &scenes, all_tests);
+        let errors = test_scen(Algorithm::Jps, &map, &scenes, all_tests);
 
         assert!(
-            errors.len() < 1,
-            "The following tests failed:\n{:?}",
-            errors
+            errors.is_empty(),
+            "The following tests failed:\n{}",
+            errors.join("\n")
         );
     }
 
@@ -99,9 +160,9 @@ mod tests {
         let errors = test_scen(Algorithm::AStar, &map, &scenes, tests);
 
         assert!(
-            errors.len() < 1,
-            "The following tests failed:\n{:?}",
-            errors
+            errors.is_empty(),
+            "The following tests failed:\n{}",
+            errors.join("\n")
         );
     }
 
@@ -115,9 +176,1436 @@ mod tests {
         let errors = test_scen(Algorithm::AStar, &map, &scenes, all_tests);
 
         assert!(
-            errors.len() < 1,
-            "The following tests failed:\n{:?}",
-            errors
+            errors.is_empty(),
+            "The following tests failed:\n{}",
+            errors.join("\n")
+        );
+    }
+
+    #[test]
+    fn jps_fixed() {
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+        let scenes = parse_scen_file(Path::new(SCEN)).unwrap();
+        let tests = TEST_NUMS_JPS.to_vec();
+
+        let errors = test_scen(Algorithm::JpsFixed, &map, &scenes, tests);
+
+        assert!(
+            errors.is_empty(),
+            "The following tests failed:\n{}",
+            errors.join("\n")
         );
     }
+
+    #[test]
+    fn a_star_fixed() {
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+        let scenes = parse_scen_file(Path::new(SCEN)).unwrap();
+        let tests = TEST_NUMS_A_STAR.to_vec();
+
+        let errors = test_scen(Algorithm::AStarFixed, &map, &scenes, tests);
+
+        assert!(
+            errors.is_empty(),
+            "The following tests failed:\n{}",
+            errors.join("\n")
+        );
+    }
+
+    #[test]
+    fn no_path() {
+        //A wall splits the map in two
+        let tiles = "@@@@@@@\
+                     @..@..@\
+                     @..@..@\
+                     @..@..@\
+                     @@@@@@@";
+        let map = MovingAiMap::new(String::from("octile"), 5, 7, tiles.chars().collect());
+        let options = SearchOptions::default();
+
+        let a_star: Result<Route, _> = a_star_path_with(&map, (1, 1), (5, 3), &options);
+        let jps: Result<Route, _> = jps_path_with(&map, (1, 1), (5, 3), &options);
+
+        assert_eq!(a_star.err(), Some(PathError::NoPath));
+        assert_eq!(jps.err(), Some(PathError::NoPath));
+    }
+
+    #[test]
+    fn invalid_cost() {
+        let options = SearchOptions::default();
+        let successors = |step: f64| move |node: u32| vec![(node + 1, step)];
+
+        //Steps and heuristics must be finite and not negative
+        for step in [f64::NAN, f64::INFINITY, -1.0] {
+            let path: Result<Route<f64, u32>, _> =
+                blitz_path::a_star(0, 3, successors(step), |_| 0.0, &options);
+            assert_eq!(path.err(), Some(PathError::InvalidCost), "{}", step);
+
+            let path: Result<Route<f64, u32>, _> =
+                blitz_path::a_star(0, 3, successors(1.0), |_| step, &options);
+            assert_eq!(path.err(), Some(PathError::InvalidCost), "{}", step);
+        }
+
+        //Integer costs which overflow are invalid rather than wrapping or panicking
+        let path: Result<Route<u32, u32>, _> =
+            blitz_path::a_star(0, 3, |node| vec![(node + 1, u32::MAX / 2)], |_| 0, &options);
+        assert_eq!(path.err(), Some(PathError::InvalidCost));
+
+        let grid = CostGrid { cost: 1e12 };
+        let path: Result<Route<u32>, _> = a_star_path_with(&grid, (0, 1), (4, 1), &options);
+        assert_eq!(path.err(), Some(PathError::InvalidCost));
+
+        let grid = CostGrid { cost: 1e15 };
+        let path: Result<Route<Fixed>, _> = a_star_path_with(&grid, (0, 1), (4, 1), &options);
+        assert_eq!(path.err(), Some(PathError::InvalidCost));
+    }
+
+    #[test]
+    fn tie_break() {
+        use blitz_path::TieBreak;
+
+        //Many routes across an open grid have the same length
+        let grid = open_grid(24, 12, &[]);
+
+        let mut expanded = Vec::new();
+        for tie_break in [TieBreak::HigherG, TieBreak::LowerH, TieBreak::Position] {
+            let mut options = SearchOptions::default();
+            options.tie_break = tie_break;
+
+            //Each policy always gives the same route and expands the same nodes
+            let (first, first_stats) =
+                a_star_path_with_stats::<f64, _>(&grid, (1, 1), (20, 8), &options);
+            let (second, second_stats) = a_star_path_with_stats(&grid, (1, 1), (20, 8), &options);
+            assert_eq!(first.unwrap(), second.unwrap(), "{:?}", tie_break);
+            assert_eq!(
+                first_stats.expanded, second_stats.expanded,
+                "{:?}",
+                tie_break
+            );
+
+            let first: Route = jps_path_with(&grid, (1, 1), (20, 8), &options).unwrap();
+            let second: Route = jps_path_with(&grid, (1, 1), (20, 8), &options).unwrap();
+            assert_eq!(first, second, "{:?}", tie_break);
+
+            expanded.push(first_stats.expanded);
+        }
+
+        //Preferring the lowest coordinates expands nodes away from the goal
+        assert_ne!(expanded[0], expanded[2]);
+
+        //Among routes of equal length, A* takes the one through the lowest coordinates
+        let mut options = SearchOptions::default();
+        options.tie_break = TieBreak::Position;
+
+        let path: Route = a_star_path_with(&grid, (1, 1), (4, 2), &options).unwrap();
+        assert_eq!(path.steps(), vec![(4, 2), (3, 1), (2, 1), (1, 1)]);
+        let path: Route = a_star_path_with(&grid, (1, 1), (2, 4), &options).unwrap();
+        assert_eq!(path.steps(), vec![(2, 4), (1, 3), (1, 2), (1, 1)]);
+    }
+
+    #[test]
+    fn cost_types() {
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+        let scenes = parse_scen_file(Path::new(SCEN)).unwrap();
+        let options = SearchOptions::default();
+
+        for index in TEST_NUMS_JPS.iter() {
+            let scene = &scenes[*index];
+            let (start, goal) = (scene.start_pos, scene.goal_pos);
+
+            let f64_route: Route<f64> = jps_path_with(&map, start, goal, &options).unwrap();
+            let f32_route: Route<f32> = jps_path_with(&map, start, goal, &options).unwrap();
+            let u32_route: Route<u32> = jps_path_with(&map, start, goal, &options).unwrap();
+            let fixed_route: Route<Fixed> = jps_path_with(&map, start, goal, &options).unwrap();
+
+            let distance = f64_route.distance();
+            assert!((f32_route.distance().to_f64() - distance).abs() < 1e-3 * distance);
+            assert!((u32_route.distance().to_f64() - distance).abs() < 1e-2 * distance);
+            assert!((fixed_route.distance().to_f64() - distance).abs() < 1e-4 * distance);
+        }
+    }
+
+    //Open grid where the middle of the centre row costs more to move through
+    struct CostGrid {
+        cost: f64,
+    }
+
+    impl Grid for CostGrid {
+        fn width(&self) -> usize {
+            5
+        }
+
+        fn height(&self) -> usize {
+            3
+        }
+
+        fn is_passable(&self, tile: Coords2D) -> bool {
+            tile.0 < 5 && tile.1 < 3
+        }
+
+        fn cost(&self, tile: Coords2D) -> f64 {
+            if tile.1 == 1 && (1..4).contains(&tile.0) {
+                self.cost
+            } else {
+                1.0
+            }
+        }
+    }
+
+    //Open bit grid with one tile which costs more, scanning over it with the bit grid's scan
+    struct ScanGrid {
+        grid: BitGrid,
+        costly: Coords2D,
+    }
+
+    impl Grid for ScanGrid {
+        fn width(&self) -> usize {
+            self.grid.width()
+        }
+
+        fn height(&self) -> usize {
+            self.grid.height()
+        }
+
+        fn is_passable(&self, tile: Coords2D) -> bool {
+            self.grid.is_passable(tile)
+        }
+
+        fn cost(&self, tile: Coords2D) -> f64 {
+            if tile == self.costly {
+                10.0
+            } else {
+                1.0
+            }
+        }
+
+        fn scan(&self, tile: Coords2D, direction: (i32, i32)) -> Coords2D {
+            self.grid.scan(tile, direction)
+        }
+    }
+
+    #[test]
+    fn tile_costs() {
+        let options = SearchOptions::default();
+
+        let cheap: Route =
+            a_star_path_with(&CostGrid { cost: 1.0 }, (0, 1), (4, 1), &options).unwrap();
+        assert_eq!(cheap.distance(), 4.0);
+
+        let expensive: Route =
+            a_star_path_with(&CostGrid { cost: 10.0 }, (0, 1), (4, 1), &options).unwrap();
+        assert!(expensive
+            .steps()
+            .iter()
+            .all(|x| x.1 != 1 || x.0 == 0 || x.0 == 4));
+
+        let invalid: Result<Route, _> =
+            a_star_path_with(&CostGrid { cost: f64::NAN }, (0, 1), (4, 1), &options);
+        assert_eq!(invalid.err(), Some(PathError::InvalidCost));
+    }
+
+    #[test]
+    fn terrain() {
+        //Water can only be entered from water and swamp only from swamp or open ground
+        let tiles = "@@@@@@@\
+                     @..W..@\
+                     @..W..@\
+                     @.SSS.@\
+                     @@@@@@@";
+        let map = MovingAiMap::new(String::from("octile"), 5, 7, tiles.chars().collect());
+        let options = SearchOptions::default();
+
+        assert!(!map.neighbours((2, 1)).any(|x| x == (3, 1)));
+        assert!(map.neighbours((2, 2)).any(|x| x == (2, 3)));
+
+        //The route must go around the water through the swamp
+        let path: Route = a_star_path_with(&map, (1, 1), (5, 1), &options).unwrap();
+        assert!(path.iter().all(|x| x.0 != 3 || x.1 == 3), "{:?}", path);
+        assert!(path.distance() > 4.0);
+        assert_eq!(path.validate(&map, &ValidationRules::default()), Ok(()));
+
+        //Moving straight through the water is not a legal route
+        let direct = Route::from((4.0, vec![(5, 1), (4, 1), (3, 1), (2, 1), (1, 1)]));
+        assert!(matches!(
+            direct.validate(&map, &ValidationRules::default()),
+            Err(RouteError::IllegalMove { .. })
+        ));
+
+        //JPS can not jump over terrain which changes the moves allowed or costs more
+        let jps: Result<Route, _> = jps_path_with(&map, (1, 1), (5, 1), &options);
+        assert_eq!(jps.err(), Some(PathError::NonUniformGrid));
+
+        let jps: Result<Route, _> =
+            jps_path_with(&CostGrid { cost: 10.0 }, (1, 1), (3, 1), &options);
+        assert_eq!(jps.err(), Some(PathError::NonUniformGrid));
+
+        //Including when the grid's scan skips over the costly tile
+        let grid = ScanGrid {
+            grid: open_grid(7, 3, &[]),
+            costly: (3, 1),
+        };
+        assert_eq!(grid.scan((1, 1), (1, 0)), (7, 1));
+        let jps: Result<Route, _> = jps_path_with(&grid, (1, 1), (5, 1), &options);
+        assert_eq!(jps.err(), Some(PathError::NonUniformGrid));
+    }
+
+    #[test]
+    fn jps_edges() {
+        //Grids have no border, so searches can start and end on their edges
+        let grid = open_grid(8, 8, &[]);
+        for &(start, goal) in [((0, 0), (5, 3)), ((7, 7), (0, 2)), ((0, 5), (7, 0))].iter() {
+            let expected = a_star_path(&grid, start, goal).unwrap();
+            let path = jps_path(&grid, start, goal).unwrap();
+
+            assert!((path.distance() - expected.distance()).abs() < 1e-9);
+            assert_eq!(path.steps().last(), Some(&start));
+            assert_eq!(path.steps().first(), Some(&goal));
+        }
+    }
+
+    #[test]
+    fn jps_bitgrid() {
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+        let scenes = parse_scen_file(Path::new(SCEN)).unwrap();
+        let grid = BitGrid::from_grid(&map);
+
+        for index in TEST_NUMS_JPS.iter() {
+            let scene = &scenes[*index];
+            let expected = jps_path(&map, scene.start_pos, scene.goal_pos).unwrap();
+            let path = jps_path(&grid, scene.start_pos, scene.goal_pos).unwrap();
+
+            assert_eq!(expected.distance(), path.distance(), "Test #{}", index);
+            assert_eq!(expected.steps(), path.steps(), "Test #{}", index);
+        }
+    }
+
+    #[test]
+    fn graph() {
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+        let scenes = parse_scen_file(Path::new(SCEN)).unwrap();
+
+        //Searching the map as a graph should find the same routes as searching it as a grid
+        for index in TEST_NUMS_A_STAR.iter() {
+            let scene = &scenes[*index];
+            let goal = scene.goal_pos;
+            let path: Route = blitz_path::a_star(
+                scene.start_pos,
+                goal,
+                |tile| {
+                    map.neighbours(tile)
+                        .map(move |next| (next, f64::distance(tile, next)))
+                },
+                |tile| f64::distance(tile, goal),
+                &SearchOptions::default(),
+            )
+            .unwrap();
+            let expected = a_star_path(&map, scene.start_pos, goal).unwrap();
+
+            assert_eq!(expected.distance(), path.distance(), "Test #{}", index);
+            assert_eq!(expected.steps(), path.steps(), "Test #{}", index);
+        }
+
+        //Nodes in two separate cycles can not reach each other
+        let successors = |node: u32| vec![(node / 4 * 4 + (node + 1) % 4, 1)];
+        let heuristic = |_| 0;
+        let options = SearchOptions::default();
+
+        let path: Route<u32, u32> =
+            blitz_path::a_star(0, 3, successors, heuristic, &options).unwrap();
+        assert_eq!(path.distance(), 3);
+        assert_eq!(path.steps(), vec![3, 2, 1, 0]);
+
+        let path: Result<Route<u32, u32>, _> =
+            blitz_path::a_star(0, 5, successors, heuristic, &options);
+        assert_eq!(path.err(), Some(PathError::NoPath));
+
+        //An admissible but inconsistent heuristic makes the search reopen node 3
+        let edges = [(0, 1, 1), (0, 2, 2), (1, 3, 1), (2, 3, 1), (3, 4, 3)];
+        let successors = |node: u32| {
+            edges
+                .iter()
+                .filter(move |x| x.0 == node)
+                .map(|x| (x.1, x.2))
+                .collect::<Vec<_>>()
+        };
+        let heuristic = |node: u32| if node == 1 { 4 } else { 0 };
+
+        let path: Route<u32, u32> =
+            blitz_path::a_star(0, 4, successors, heuristic, &options).unwrap();
+        assert_eq!(path.distance(), 5);
+        assert_eq!(path.steps(), vec![4, 3, 1, 0]);
+
+        //Steps must have valid costs
+        let path: Result<Route<f64, u32>, _> =
+            blitz_path::a_star(0, 3, |node| vec![(node + 1, f64::NAN)], |_| 0.0, &options);
+        assert_eq!(path.err(), Some(PathError::InvalidCost));
+    }
+
+    #[test]
+    fn navmesh() {
+        //A winding corridor of unit squares, some listed clockwise and some anticlockwise
+        let cells = [(0, 0), (1, 0), (1, 1), (1, 2), (2, 2)];
+        let mut vertices = Vec::new();
+        let mut polygons = Vec::new();
+        for (index, &(x, y)) in cells.iter().enumerate() {
+            let mut polygon = Vec::new();
+            for corner in [(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)].iter() {
+                let point = (corner.0 as f64, corner.1 as f64);
+                match vertices.iter().position(|x| *x == point) {
+                    Some(vertex) => polygon.push(vertex),
+                    None => {
+                        vertices.push(point);
+                        polygon.push(vertices.len() - 1);
+                    }
+                }
+            }
+            if index % 2 == 1 {
+                polygon.reverse();
+            }
+            polygons.push(polygon);
+        }
+        let mesh = NavMesh::new(vertices, polygons);
+
+        let route = navmesh_path(&mesh, (0.2, 0.5), (2.9, 2.5)).unwrap();
+        let expected: Vec<(f64, f64)> = vec![(2.9, 2.5), (2.0, 2.0), (1.0, 1.0), (0.2, 0.5)];
+        let distance: f64 = expected
+            .windows(2)
+            .map(|x| ((x[0].0 - x[1].0).powi(2) + (x[0].1 - x[1].1).powi(2)).sqrt())
+            .sum();
+        assert_eq!(route.steps(), expected);
+        assert!((route.distance() - distance).abs() < 1e-9);
+
+        let route = navmesh_path(&mesh, (2.9, 2.5), (0.2, 0.5)).unwrap();
+        assert_eq!(
+            route.steps(),
+            vec![(0.2, 0.5), (1.0, 1.0), (2.0, 2.0), (2.9, 2.5)]
+        );
+
+        //Routes without corners go straight to the goal
+        let route = navmesh_path(&mesh, (1.2, 0.2), (1.8, 2.8)).unwrap();
+        assert_eq!(route.steps(), vec![(1.8, 2.8), (1.2, 0.2)]);
+
+        //Points outside the mesh can not be reached
+        let route = navmesh_path_with(&mesh, (0.5, 0.5), (0.5, 2.5), &SearchOptions::default());
+        assert_eq!(route.err(), Some(PathError::NoPath));
+    }
+
+    #[test]
+    fn navmesh_grid() {
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+        let scenes = parse_scen_file(Path::new(SCEN)).unwrap();
+        let mesh = NavMesh::from_grid(&map);
+
+        //Merging tiles into rectangles should give a much smaller graph
+        assert!(mesh.polygons().len() * 100 < map.width() * map.height());
+
+        for index in TEST_NUMS_JPS.iter() {
+            let scene = &scenes[*index];
+            let start = (scene.start_pos.0 as f64, scene.start_pos.1 as f64);
+            let goal = (scene.goal_pos.0 as f64, scene.goal_pos.1 as f64);
+            let route = navmesh_path(&mesh, start, goal).unwrap();
+
+            assert!(
+                route.distance() <= scene.optimal_length + 1e-6,
+                "Test #{}",
+                index
+            );
+            assert_eq!(route.steps()[0], goal, "Test #{}", index);
+            assert_eq!(route.steps().last(), Some(&start), "Test #{}", index);
+        }
+
+        //Routes must go around a single blocked tile, whose neighbours meet it at T-junctions
+        let grid = open_grid(3, 3, &[(1, 1)]);
+        let mesh = NavMesh::from_grid(&grid);
+        let route = navmesh_path(&mesh, (1.0, 0.0), (1.0, 2.0)).unwrap();
+        assert!((route.distance() - (1.0 + 2f64.sqrt())).abs() < 1e-9);
+        assert_eq!(route.steps().len(), 4);
+    }
+
+    #[test]
+    fn visibility() {
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+        let scenes = parse_scen_file(Path::new(SCEN)).unwrap();
+        let graph = VisibilityGraph::from_grid(&map);
+        let mesh = NavMesh::from_grid(&map);
+
+        //Both give the shortest route moving at any angle
+        for index in TEST_NUMS_JPS.iter() {
+            let scene = &scenes[*index];
+            let start = (scene.start_pos.0 as f64, scene.start_pos.1 as f64);
+            let goal = (scene.goal_pos.0 as f64, scene.goal_pos.1 as f64);
+            let route = visibility_path(&graph, scene.start_pos, scene.goal_pos).unwrap();
+            let expected = navmesh_path(&mesh, start, goal).unwrap();
+
+            assert!(
+                route.distance() <= scene.optimal_length + 1e-6,
+                "Test #{}",
+                index
+            );
+            assert!(
+                (route.distance() - expected.distance()).abs() < 1e-6,
+                "Test #{}",
+                index
+            );
+            assert_eq!(route.steps()[0], goal, "Test #{}", index);
+            assert_eq!(route.steps().last(), Some(&start), "Test #{}", index);
+        }
+
+        //Routes can not squeeze between impassable tiles which touch at a corner
+        let grid = open_grid(4, 4, &[(1, 1), (2, 2)]);
+        let graph = VisibilityGraph::from_grid(&grid);
+
+        let route = visibility_path(&graph, (2, 1), (1, 2)).unwrap();
+        assert!((route.distance() - (2.0 + 2f64.sqrt())).abs() < 1e-9);
+
+        let route = visibility_path(&graph, (0, 0), (3, 0)).unwrap();
+        assert_eq!(route.steps(), vec![(3.0, 0.0), (0.0, 0.0)]);
+
+        let route = visibility_path_with(&graph, (0, 0), (1, 1), &SearchOptions::default());
+        assert_eq!(route.err(), Some(PathError::NoPath));
+
+        //As with line_of_sight, a line through the corner of an impassable tile is not clear
+        let grid = open_grid(3, 3, &[(1, 0)]);
+        let graph = VisibilityGraph::from_grid(&grid);
+        assert!(!blitz_path::line_of_sight(&grid, (0, 0), (2, 2)));
+
+        //The route turns at the corner rather than passing straight through it
+        let route = visibility_path(&graph, (0, 0), (2, 2)).unwrap();
+        assert_eq!(route.steps(), vec![(2.0, 2.0), (0.5, 0.5), (0.0, 0.0)]);
+    }
+
+    #[test]
+    fn line_of_sight() {
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+
+        //Sight must follow the same rules as moving between neighbouring tiles
+        for y in 0..64 {
+            for x in 0..64 {
+                for (dx, dy) in [(1, 0), (0, 1), (1, 1), (1, -1)].iter() {
+                    let next = ((x + dx) as usize, (y + dy) as usize);
+                    let tile = (x as usize, y as usize);
+                    let neighbour =
+                        map.is_passable(tile) && map.neighbours(tile).any(|x| x == next);
+
+                    assert_eq!(neighbour, blitz_path::line_of_sight(&map, tile, next));
+                }
+            }
+        }
+
+        //Sight is the same in both directions
+        let scenes = parse_scen_file(Path::new(SCEN)).unwrap();
+        for scene in scenes.iter().step_by(25) {
+            let (a, b) = (scene.start_pos, scene.goal_pos);
+            let (c, d) = ((a.0, b.1), (b.0, a.1));
+
+            assert_eq!(
+                blitz_path::line_of_sight(&map, a, b),
+                blitz_path::line_of_sight(&map, b, a)
+            );
+            assert_eq!(
+                blitz_path::line_of_sight(&map, c, d),
+                blitz_path::line_of_sight(&map, d, c)
+            );
+        }
+
+        //Rays stop before impassable tiles and the edge of the map
+        let grid = open_grid(4, 4, &[(2, 1), (1, 2)]);
+        assert_eq!(blitz_path::raycast(&grid, (0, 0), (1.0, 1.0)), Some((1, 1)));
+        assert_eq!(blitz_path::raycast(&grid, (0, 0), (1.0, 0.0)), Some((3, 0)));
+        assert_eq!(
+            blitz_path::raycast(&grid, (3, 3), (-1.0, 0.0)),
+            Some((0, 3))
+        );
+        assert_eq!(blitz_path::raycast(&grid, (0, 0), (3.0, 1.0)), Some((1, 0)));
+        assert!(!blitz_path::line_of_sight(&grid, (1, 1), (2, 2)));
+        assert!(blitz_path::line_of_sight(&grid, (0, 0), (1, 1)));
+    }
+
+    #[test]
+    fn simplify() {
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+        let scenes = parse_scen_file(Path::new(SCEN)).unwrap();
+
+        for index in TEST_NUMS_JPS.iter() {
+            let scene = &scenes[*index];
+            let path = a_star_path(&map, scene.start_pos, scene.goal_pos).unwrap();
+            let simple = path.simplify(&map);
+            let (steps, waypoints) = (path.steps(), simple.steps());
+
+            assert!(waypoints.len() < steps.len(), "Test #{}", index);
+            assert!(
+                simple.distance() <= path.distance() + 1e-6,
+                "Test #{}",
+                index
+            );
+            assert_eq!(waypoints.first(), steps.first(), "Test #{}", index);
+            assert_eq!(waypoints.last(), steps.last(), "Test #{}", index);
+            for pair in waypoints.windows(2) {
+                assert!(
+                    blitz_path::line_of_sight(&map, pair[0], pair[1]),
+                    "Test #{}",
+                    index
+                );
+            }
+        }
+
+        //Straight routes become a single line, while routes around corners keep the corner
+        let grid = open_grid(3, 3, &[(1, 1)]);
+        let path = a_star_path(&grid, (0, 0), (2, 0)).unwrap().simplify(&grid);
+        assert_eq!(path.steps(), vec![(2, 0), (0, 0)]);
+        assert_eq!(path.distance(), 2.0);
+
+        let path = a_star_path(&grid, (0, 0), (2, 2)).unwrap().simplify(&grid);
+        assert_eq!(path.steps().len(), 3);
+        assert_eq!(path.distance(), 4.0);
+
+        //Waypoints are measured along the line between them, not as octile distances
+        let grid = open_grid(5, 5, &[]);
+        let path: Route<u32> = a_star_path_with(&grid, (0, 0), (3, 1), &SearchOptions::default())
+            .unwrap()
+            .simplify(&grid);
+        assert_eq!(path.steps(), vec![(3, 1), (0, 0)]);
+        assert_eq!(path.distance(), 32);
+
+        let mut rules = ValidationRules::default();
+        rules.neighbourhood = Neighbourhood::AnyAngle;
+        assert_eq!(path.validate(&grid, &rules), Ok(()));
+    }
+
+    #[test]
+    fn smooth() {
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+        let scenes = parse_scen_file(Path::new(SCEN)).unwrap();
+
+        for index in TEST_NUMS_JPS.iter() {
+            let scene = &scenes[*index];
+            let path = a_star_path(&map, scene.start_pos, scene.goal_pos).unwrap();
+            let spline = path.smooth(&map);
+            let start = (scene.start_pos.0 as f64, scene.start_pos.1 as f64);
+            let goal = (scene.goal_pos.0 as f64, scene.goal_pos.1 as f64);
+
+            //The curve should be about as long as the route, and never cross an impassable tile
+            assert!(spline.length() + 1e-6 >= distance(scene.start_pos, scene.goal_pos));
+            assert!(spline.length() <= path.distance() * 1.1, "Test #{}", index);
+            assert_eq!(spline.controls().first(), Some(&start), "Test #{}", index);
+            assert_eq!(spline.controls().last(), Some(&goal), "Test #{}", index);
+
+            let points = spline.sample(0.25);
+            assert_eq!(points.first(), Some(&start), "Test #{}", index);
+            assert_eq!(points.last(), Some(&goal), "Test #{}", index);
+            for pair in points.windows(2) {
+                let gap =
+                    ((pair[0].0 - pair[1].0).powi(2) + (pair[0].1 - pair[1].1).powi(2)).sqrt();
+                assert!(gap <= 0.25 + 1e-6, "Test #{}", index);
+            }
+            for point in points.iter() {
+                let tile = (
+                    (point.0 + 0.5).floor() as usize,
+                    (point.1 + 0.5).floor() as usize,
+                );
+                assert!(map.is_passable(tile), "Test #{} {:?}", index, point);
+            }
+        }
+
+        //Empty routes give an empty curve
+        let path = a_star_path(&map, scenes[0].start_pos, scenes[0].start_pos).unwrap();
+        let spline = path.smooth(&map);
+        assert_eq!(spline.length(), 0.0);
+        assert_eq!(spline.point_at(0.0), None);
+        assert!(spline.sample(1.0).is_empty());
+    }
+
+    #[test]
+    fn route_api() {
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+        let scene = &parse_scen_file(Path::new(SCEN)).unwrap()[34];
+        let mut path = a_star_path(&map, scene.start_pos, scene.goal_pos).unwrap();
+        let steps = path.steps();
+
+        assert_eq!(path.as_slice(), &steps[..]);
+        assert_eq!(path.len(), steps.len());
+        assert!(!path.is_empty());
+        assert_eq!(path[0], scene.goal_pos);
+        assert_eq!(path.goal(), Some(&scene.goal_pos));
+        assert_eq!(path.start(), Some(&scene.start_pos));
+        assert!(path.iter().eq(steps.iter()));
+        assert!(path.iter_forward().eq(steps.iter().rev()));
+
+        path.reverse();
+        assert_eq!(path.goal(), Some(&scene.start_pos));
+        assert_eq!(path.start(), Some(&scene.goal_pos));
+        assert!(path.iter_forward().eq(steps.iter()));
+
+        let empty = a_star_path(&map, scene.start_pos, scene.start_pos).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.start(), None);
+        assert_eq!(empty.into_steps(), Vec::new());
+    }
+
+    #[test]
+    fn validate() {
+        let grid = open_grid(3, 3, &[(1, 1)]);
+        let mut rules = ValidationRules::default();
+        rules.start = Some((0, 0));
+        rules.goal = Some((2, 2));
+        let route = |distance: f64, steps: Vec<Coords2D>| Route::from((distance, steps));
+
+        let path = a_star_path(&grid, (0, 0), (2, 2)).unwrap();
+        assert_eq!(path.validate(&grid, &rules), Ok(()));
+
+        let mut four = rules;
+        four.neighbourhood = Neighbourhood::Four;
+        assert_eq!(path.validate(&grid, &four), Ok(()));
+
+        //Moves past the corner of the impassable tile
+        let cut = route(2.0 * 2f64.sqrt(), vec![(2, 2), (1, 2), (0, 1), (0, 0)]);
+        assert_eq!(
+            cut.validate(&grid, &rules),
+            Err(RouteError::IllegalMove {
+                index: 1,
+                from: (0, 1),
+                to: (1, 2)
+            })
+        );
+        let mut cutting = rules;
+        cutting.neighbourhood = Neighbourhood::EightCuttingCorners;
+        assert_eq!(
+            cut.validate(&grid, &cutting),
+            Err(RouteError::WrongDistance {
+                expected: 2.0 + 2f64.sqrt(),
+                found: 2.0 * 2f64.sqrt()
+            })
+        );
+
+        let through = route(2.0 * 2f64.sqrt(), vec![(2, 2), (1, 1), (0, 0)]);
+        assert_eq!(
+            through.validate(&grid, &rules),
+            Err(RouteError::Impassable {
+                index: 1,
+                step: (1, 1)
+            })
+        );
+
+        let short = route(2.0, vec![(2, 0), (1, 0), (0, 0)]);
+        assert_eq!(
+            short.validate(&grid, &rules),
+            Err(RouteError::WrongGoal {
+                expected: (2, 2),
+                found: Some((2, 0))
+            })
+        );
+
+        //Simplified routes need to be checked by line of sight
+        let simple = path.simplify(&grid);
+        let mut any_angle = rules;
+        any_angle.neighbourhood = Neighbourhood::AnyAngle;
+        assert!(simple.validate(&grid, &rules).is_err());
+        assert_eq!(simple.validate(&grid, &any_angle), Ok(()));
+
+        //Long routes allow for rounding relative to their length
+        let mut cutting = ValidationRules::default();
+        cutting.neighbourhood = Neighbourhood::EightCuttingCorners;
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+        let scenes = parse_scen_file(Path::new(SCEN)).unwrap();
+        for index in TEST_NUMS_JPS.iter() {
+            let scene = &scenes[*index];
+            let path: Route<f32> = jps_path_with(
+                &map,
+                scene.start_pos,
+                scene.goal_pos,
+                &SearchOptions::default(),
+            )
+            .unwrap();
+            assert_eq!(path.validate(&map, &cutting), Ok(()), "Test #{}", index);
+        }
+    }
+
+    #[test]
+    fn compact() {
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+        let scenes = parse_scen_file(Path::new(SCEN)).unwrap();
+
+        for &num in TEST_NUMS_A_STAR.iter() {
+            let scene = &scenes[num];
+            let path = a_star_path(&map, scene.start_pos, scene.goal_pos).unwrap();
+            let compact = path.compress().unwrap();
+            let bytes = compact.to_bytes();
+            let decoded = CompactRoute::from_bytes(&bytes, &map).unwrap();
+            let route: Route = decoded.to_route();
+
+            assert_eq!(decoded, compact);
+            assert_eq!(route.as_slice(), path.as_slice());
+            assert!((route.distance() - path.distance()).abs() < 1e-6);
+            assert!(bytes.len() <= 4 + compact.segments().len() * 2);
+        }
+
+        //Runs are merged, and routes which skip tiles can't be compressed
+        let route = Route::from((3.0, vec![(3, 0), (2, 0), (1, 0), (0, 0)]));
+        let compact = route.compress().unwrap();
+        assert_eq!(compact.start(), Some((0, 0)));
+        let segments: &[Segment] = compact.segments();
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].direction(), segments[0].run()), ((1, 0), 3));
+        assert_eq!(Route::from((2.0, vec![(2, 0), (0, 0)])).compress(), None);
+
+        //Empty, truncated and off-map routes
+        let empty = CompactRoute::default();
+        assert_eq!(CompactRoute::from_bytes(&[], &map), Some(empty));
+        assert_eq!(CompactRoute::from_bytes(&[0x80], &map), None);
+        assert_eq!(CompactRoute::from_bytes(&[1], &map), None);
+        assert_eq!(CompactRoute::from_bytes(&[1, 0, 2 << 3 | 4], &map), None);
+        assert_eq!(CompactRoute::from_bytes(&[1, 0, 4], &map), None);
+
+        //Runs past the far edges of the map are rejected before anything is allocated
+        let write_varint = |bytes: &mut Vec<u8>, mut value: u64| {
+            while value >= 0x80 {
+                bytes.push(value as u8 | 0x80);
+                value >>= 7;
+            }
+            bytes.push(value as u8);
+        };
+        let mut bytes = vec![1, 1];
+        write_varint(&mut bytes, (map.width() as u64) << 3);
+        assert_eq!(CompactRoute::from_bytes(&bytes, &map), None);
+        let mut bytes = vec![1, 1];
+        write_varint(&mut bytes, (u32::MAX as u64) << 3 | 2);
+        assert_eq!(CompactRoute::from_bytes(&bytes, &map), None);
+        let mut bytes = Vec::new();
+        write_varint(&mut bytes, map.width() as u64);
+        bytes.push(0);
+        assert_eq!(CompactRoute::from_bytes(&bytes, &map), None);
+
+        //As are routes moving back and forth more times than the map has tiles
+        let grid = open_grid(8, 8, &[]);
+        let mut bytes = vec![0, 0];
+        for _ in 0..500 {
+            write_varint(&mut bytes, 7 << 3);
+            write_varint(&mut bytes, 7 << 3 | 4);
+        }
+        assert_eq!(CompactRoute::from_bytes(&bytes, &grid), None);
+        bytes.truncate(2 + 8);
+        let route: Route = CompactRoute::from_bytes(&bytes, &grid).unwrap().to_route();
+        assert_eq!(route.len(), 8 * 7 + 1);
+    }
+
+    #[test]
+    fn schedule() {
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+        let scene = &parse_scen_file(Path::new(SCEN)).unwrap()[34];
+        let path = a_star_path(&map, scene.start_pos, scene.goal_pos).unwrap();
+
+        let schedule = path.schedule(|_| 2.0).unwrap();
+        let arrivals = schedule.arrivals();
+        assert!((schedule.eta() - path.distance() / 2.0).abs() < 1e-6);
+        assert!(arrivals.iter().map(|x| x.1).eq(path.iter().copied()));
+        assert!(arrivals.windows(2).all(|pair| pair[0].0 > pair[1].0));
+        for &(time, step) in arrivals.iter() {
+            assert_eq!(
+                schedule.position_at(time),
+                Some((step.0 as f64, step.1 as f64))
+            );
+        }
+        assert_eq!(
+            schedule.position_at(-1.0),
+            Some((scene.start_pos.0 as f64, scene.start_pos.1 as f64))
+        );
+
+        //Slow tiles take longer to cross
+        let route = Route::from((2.0, vec![(2, 0), (1, 0), (0, 0)]));
+        let schedule = route
+            .schedule(|tile| if tile == (1, 0) { 0.5 } else { 1.0 })
+            .unwrap();
+        let times: Vec<f64> = schedule.arrivals().iter().map(|x| x.0).collect();
+        assert_eq!(times, vec![3.0, 1.5, 0.0]);
+        assert_eq!(schedule.position_at(0.5), Some((0.5, 0.0)));
+        assert_eq!(schedule.position_at(1.0), Some((0.75, 0.0)));
+        assert_eq!(schedule.position_at(10.0), Some((2.0, 0.0)));
+
+        assert_eq!(
+            route.schedule(|tile| if tile == (2, 0) { 0.0 } else { 1.0 }),
+            Err(RouteError::InvalidSpeed {
+                index: 0,
+                step: (2, 0)
+            })
+        );
+        assert_eq!(
+            route.schedule(|tile| if tile == (1, 0) { f64::NAN } else { 1.0 }),
+            Err(RouteError::InvalidSpeed {
+                index: 1,
+                step: route.steps()[1]
+            })
+        );
+        assert_eq!(
+            Route::<f64>::from((0.0, Vec::new())).schedule(|_| 1.0),
+            Ok(Schedule::default())
+        );
+        assert_eq!(Schedule::default().position_at(0.0), None);
+    }
+
+    #[test]
+    fn edit_routes() {
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+        let scene = &parse_scen_file(Path::new(SCEN)).unwrap()[34];
+        let path = a_star_path(&map, scene.start_pos, scene.goal_pos).unwrap();
+        let middle = path.len() / 2;
+
+        //Split the route at its middle step and join it back together
+        let first = path.sub_route(&map, middle..);
+        let last = path.sub_route(&map, ..=middle);
+        assert_eq!(first.goal(), Some(&path[middle]));
+        assert_eq!(last.start(), Some(&path[middle]));
+        assert!((first.distance() + last.distance() - path.distance()).abs() < 1e-9);
+
+        let joined = first.clone().join(last.clone()).unwrap();
+        assert_eq!(joined.as_slice(), path.as_slice());
+        assert!((joined.distance() - path.distance()).abs() < 1e-9);
+        assert_eq!(last.clone().join(first.clone()), None);
+        assert_eq!(
+            first.clone().join(Route::from((0.0, Vec::new()))),
+            Some(first)
+        );
+
+        //Block a step and replan around it
+        let mut grid = BitGrid::from_grid(&map);
+        grid.set_passable(path[middle], false);
+        let (from, to) = (path[middle + 3], path[middle - 3]);
+        let detour = a_star_path(&grid, from, to).unwrap();
+        let spliced = path.splice(&grid, &detour).unwrap();
+
+        let mut rules = ValidationRules::default();
+        rules.start = Some(scene.start_pos);
+        rules.goal = Some(scene.goal_pos);
+        assert_eq!(spliced.validate(&grid, &rules), Ok(()));
+        assert!(!spliced.iter().any(|step| *step == path[middle]));
+        assert!(spliced.distance() >= path.distance());
+
+        let mut backwards = detour.clone();
+        backwards.reverse();
+        assert_eq!(path.splice(&grid, &backwards), None);
+        assert_eq!(path.splice(&grid, &Route::from((0.0, Vec::new()))), None);
+    }
+
+    #[test]
+    fn follow() {
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+        let scene = &parse_scen_file(Path::new(SCEN)).unwrap()[34];
+        let path = a_star_path(&map, scene.start_pos, scene.goal_pos).unwrap();
+        let steps: Vec<Coords2D> = path.iter_forward().copied().collect();
+
+        //Move along the route in small steps, wobbling to either side
+        let mut follower = PathFollower::new(path.clone(), 0.5);
+        for (index, pair) in steps.windows(2).enumerate() {
+            let (dx, dy) = (
+                pair[1].0 as f64 - pair[0].0 as f64,
+                pair[1].1 as f64 - pair[0].1 as f64,
+            );
+            let length = (dx * dx + dy * dy).sqrt();
+
+            for part in 0..4 {
+                let fraction = part as f64 / 4.0;
+                let wobble = if part % 2 == 0 { 0.2 } else { -0.2 } / length;
+                let position = (
+                    pair[0].0 as f64 + dx * fraction - dy * wobble,
+                    pair[0].1 as f64 + dy * fraction + dx * wobble,
+                );
+
+                let status = follower.update(&map, position);
+                assert!(!status.needs_replan(), "{:?} at {:?}", status, position);
+                assert!((index + 1..=index + 2).contains(&follower.index()));
+            }
+        }
+        let goal = (scene.goal_pos.0 as f64, scene.goal_pos.1 as f64);
+        assert_eq!(follower.update(&map, goal), FollowStatus::Arrived);
+        assert_eq!(follower.target(), None);
+        assert!(follower.remaining().is_empty());
+
+        //Drifting away from the route
+        let mut follower = PathFollower::new(path.clone(), 0.5);
+        let start = (scene.start_pos.0 as f64, scene.start_pos.1 as f64);
+        assert_eq!(
+            follower.update(&map, start),
+            FollowStatus::Following { target: steps[1] }
+        );
+        let status = follower.update(&map, (start.0 + 3.0, start.1 + 4.0));
+        assert!(status.needs_replan());
+        assert!(matches!(status, FollowStatus::OffPath { deviation } if deviation > 0.5));
+
+        //Cutting a corner moves straight onto a later part of the route
+        let open = open_grid(2, 2, &[]);
+        let route = Route::from((2.0, vec![(1, 1), (1, 0), (0, 0)]));
+        let mut follower = PathFollower::new(route.clone(), 0.5);
+        assert_eq!(follower.update(&open, (1.0, 1.0)), FollowStatus::Arrived);
+        let mut follower = PathFollower::new(route, 0.25);
+        assert_eq!(
+            follower.update(&open, (1.0, 0.5)),
+            FollowStatus::Following { target: (1, 1) }
+        );
+        assert_eq!(follower.index(), 2);
+
+        //The next step becoming blocked
+        let mut grid = BitGrid::from_grid(&map);
+        grid.set_passable(steps[2], false);
+        let mut follower = PathFollower::new(path.clone(), 0.5);
+        let position = (steps[1].0 as f64, steps[1].1 as f64);
+        assert_eq!(
+            follower.update(&grid, position),
+            FollowStatus::Blocked { step: steps[2] }
+        );
+        assert_eq!(follower.remaining(), &path.as_slice()[..steps.len() - 2]);
+        assert_eq!(follower.into_route(), path);
+    }
+
+    #[test]
+    fn stats() {
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+        let scenes = parse_scen_file(Path::new(SCEN)).unwrap();
+        let options = SearchOptions::default();
+
+        for index in TEST_NUMS_A_STAR.iter() {
+            let scene = &scenes[*index];
+            let (start, goal) = (scene.start_pos, scene.goal_pos);
+
+            let (a_star, a_star_stats) = a_star_path_with_stats(&map, start, goal, &options);
+            let (jps, jps_stats) = jps_path_with_stats(&map, start, goal, &options);
+            let (a_star, jps): (Route, Route) = (a_star.unwrap(), jps.unwrap());
+
+            assert_eq!(a_star, a_star_path(&map, start, goal).unwrap());
+            assert_eq!(jps, jps_path(&map, start, goal).unwrap());
+
+            for stats in [a_star_stats, jps_stats].iter() {
+                assert!(stats.expanded > 0, "Test #{}", index);
+                assert!(stats.generated > stats.expanded, "Test #{}", index);
+                assert!(stats.max_open <= stats.generated, "Test #{}", index);
+            }
+            assert!(a_star_stats.expanded >= a_star.len() - 1, "Test #{}", index);
+            assert_eq!(a_star_stats.jump_points, 0, "Test #{}", index);
+            assert!(jps_stats.jump_points > 0, "Test #{}", index);
+            assert_eq!(jps_stats.reopened, 0, "Test #{}", index);
+        }
+
+        //Failed searches still report their work
+        let tiles = "@@@@@\
+                     @.@.@\
+                     @@@@@";
+        let map = MovingAiMap::new(String::from("octile"), 3, 5, tiles.chars().collect());
+        let (path, stats) = a_star_path_with_stats::<f64, _>(&map, (1, 1), (3, 1), &options);
+        assert_eq!(path, Err(PathError::NoPath));
+        assert_eq!(stats.expanded, 1);
+        assert_eq!(stats.generated, 1);
+
+        let (_, stats) = a_star_path_with_stats::<f64, _>(&map, (1, 1), (1, 1), &options);
+        assert_eq!(
+            stats,
+            SearchStats {
+                elapsed: stats.elapsed,
+                ..SearchStats::default()
+            }
+        );
+    }
+
+    #[test]
+    fn batch() {
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+        let scenes = parse_scen_file(Path::new(SCEN)).unwrap();
+        let options = SearchOptions::default();
+
+        //Include a query with no steps
+        let mut queries: Vec<(Coords2D, Coords2D)> = TEST_NUMS_A_STAR
+            .iter()
+            .chain(TEST_NUMS_A_STAR.iter().rev())
+            .map(|index| (scenes[*index].start_pos, scenes[*index].goal_pos))
+            .collect();
+        queries.push((queries[0].0, queries[0].0));
+
+        let paths = find_paths(&map, &queries);
+        assert_eq!(paths.len(), queries.len());
+        for (query, path) in queries.iter().zip(paths.iter()) {
+            assert_eq!(path, &a_star_path(&map, query.0, query.1), "{:?}", query);
+        }
+        assert_eq!(paths[paths.len() - 1].as_ref().map(Route::len), Some(0));
+
+        let fixed: Vec<Result<Route<Fixed>, PathError>> = find_paths_with(&map, &queries, &options);
+        for (query, path) in queries.iter().zip(fixed.iter()) {
+            assert_eq!(
+                path.as_ref().ok(),
+                a_star_path_fixed(&map, query.0, query.1).as_ref()
+            );
+        }
+
+        //Contexts can be reused on different grids
+        let mut context = SearchContext::new();
+        let grid = BitGrid::from_grid(&map);
+        for (start, goal) in queries.iter() {
+            let expected = a_star_path_with(&map, *start, *goal, &options);
+            let first: Result<Route, _> = context.a_star_path(&map, *start, *goal, &options);
+            let second: Result<Route, _> = context.a_star_path(&grid, *start, *goal, &options);
+
+            assert_eq!(first, expected);
+            assert_eq!(second, expected);
+        }
+        assert!(format!("{:?}", context).starts_with("SearchContext"));
+    }
+
+    #[test]
+    fn render_routes() {
+        let tiles = "@@@@@@@\
+                     @..@..@\
+                     @.....@\
+                     @@@@@@@";
+        let map = MovingAiMap::new(String::from("octile"), 4, 7, tiles.chars().collect());
+        let path = a_star_path(&map, (1, 1), (5, 1)).unwrap();
+        let mut overlay = Overlay::from_route(&path);
+        overlay.expanded = vec![(1, 1), (2, 1), (1, 2), (2, 2)];
+
+        assert_eq!(
+            render_ascii(&map, &overlay),
+            "@@@@@@@\n\
+             @Sx@.G@\n\
+             @x***.@\n\
+             @@@@@@@\n"
+        );
+
+        //Drawing only the area around the route
+        overlay.area = overlay.bounds(0);
+        assert_eq!(overlay.area, Some(((1, 1), (5, 2))));
+        assert_eq!(render_ascii(&map, &overlay), "Sx@.G\nx***.\n");
+
+        let image = render_ppm(&map, &overlay);
+        let header = b"P6\n5 2\n255\n";
+        assert_eq!(&image[..header.len()], header);
+        assert_eq!(image.len(), header.len() + 5 * 2 * 3);
+        assert_eq!(&image[header.len()..header.len() + 3], &[0, 180, 0]);
+
+        //Marking a start and goal without a route
+        let overlay = Overlay {
+            start: Some((1, 1)),
+            goal: Some((5, 1)),
+            ..Overlay::default()
+        };
+        assert_eq!(overlay.bounds(0), Some(((1, 1), (5, 1))));
+        assert_eq!(render_ascii(&map, &overlay).lines().nth(1), Some("@S.@.G@"));
+
+        //Nothing is drawn for an empty grid or an area outside the grid
+        let empty = BitGrid::new(0, 0);
+        assert_eq!(render_ascii(&empty, &Overlay::default()), "");
+        assert_eq!(render_ppm(&empty, &Overlay::default()), b"P6\n0 0\n255\n");
+        let outside = Overlay {
+            area: Some(((10, 10), (12, 12))),
+            ..Overlay::default()
+        };
+        assert_eq!(render_ascii(&map, &outside), "");
+        assert_eq!(render_ppm(&map, &outside), b"P6\n0 0\n255\n");
+
+        assert_eq!(Overlay::default().bounds(1), None);
+        assert_eq!(render_ascii(&map, &Overlay::default()).lines().count(), 4);
+    }
+
+    #[cfg(feature = "observer")]
+    #[derive(Default)]
+    struct Recorder {
+        pushed: Vec<Coords2D>,
+        expanded: Vec<Coords2D>,
+        closed: Vec<Coords2D>,
+        found: Option<(Vec<Coords2D>, f64)>,
+    }
+
+    #[cfg(feature = "observer")]
+    impl SearchObserver for Recorder {
+        fn pushed(&mut self, node: Coords2D, _parent: Coords2D, g: f64, h: f64) {
+            assert!(g >= 0.0 && h >= 0.0);
+            self.pushed.push(node);
+        }
+
+        fn expanded(&mut self, node: Coords2D) {
+            self.expanded.push(node);
+        }
+
+        fn closed(&mut self, node: Coords2D) {
+            assert_eq!(self.expanded.last(), Some(&node));
+            self.closed.push(node);
+        }
+
+        fn found(&mut self, steps: &[Coords2D], distance: f64) {
+            self.found = Some((steps.to_vec(), distance));
+        }
+    }
+
+    #[cfg(feature = "observer")]
+    #[test]
+    fn observer() {
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+        let scenes = parse_scen_file(Path::new(SCEN)).unwrap();
+        let options = SearchOptions::default();
+
+        for index in TEST_NUMS_A_STAR.iter() {
+            let scene = &scenes[*index];
+            let (start, goal) = (scene.start_pos, scene.goal_pos);
+
+            let mut a_star = Recorder::default();
+            let mut jps = Recorder::default();
+            let a_star_route: Route =
+                a_star_path_observed(&map, start, goal, &options, &mut a_star).unwrap();
+            let jps_route: Route =
+                jps_path_observed(&map, start, goal, &options, &mut jps).unwrap();
+            let (_, a_star_stats) = a_star_path_with_stats::<f64, _>(&map, start, goal, &options);
+            let (_, jps_stats) = jps_path_with_stats::<f64, _>(&map, start, goal, &options);
+
+            for (recorder, route, stats) in [
+                (a_star, a_star_route, a_star_stats),
+                (jps, jps_route, jps_stats),
+            ]
+            .iter()
+            {
+                let (steps, distance) = recorder.found.clone().unwrap();
+                assert_eq!(steps.as_slice(), route.as_slice(), "Test #{}", index);
+                assert_eq!(distance, route.distance(), "Test #{}", index);
+
+                assert_eq!(recorder.expanded, recorder.closed, "Test #{}", index);
+                assert_eq!(recorder.expanded.len(), stats.expanded, "Test #{}", index);
+                assert_eq!(recorder.pushed.len(), stats.generated, "Test #{}", index);
+                assert_eq!(recorder.pushed[0], start, "Test #{}", index);
+                assert_eq!(recorder.pushed.iter().filter(|x| **x == start).count(), 1);
+                assert!(recorder.pushed.contains(&goal), "Test #{}", index);
+            }
+
+            //Overlays record the search for drawing
+            let mut overlay = Overlay::default();
+            let route: Route =
+                jps_path_observed(&map, start, goal, &options, &mut overlay).unwrap();
+            assert_eq!(overlay.route.as_slice(), route.as_slice());
+            assert_eq!(overlay.expanded.len(), jps_stats.expanded);
+            assert_eq!(overlay.jump_points.len(), jps_stats.jump_points);
+            assert!(overlay.jump_points.contains(&goal));
+
+            let mut overlay = Overlay::default();
+            let _: Route = a_star_path_observed(&map, start, goal, &options, &mut overlay).unwrap();
+            assert!(overlay.jump_points.is_empty());
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use blitz_path::{Spline, TieBreak, VisibilityGraph};
+
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+        let scene = &parse_scen_file(Path::new(SCEN)).unwrap()[34];
+
+        let path = a_star_path(&map, scene.start_pos, scene.goal_pos).unwrap();
+        let json = serde_json::to_string(&path).unwrap();
+        assert_eq!(serde_json::from_str::<Route>(&json).unwrap(), path);
+
+        let path = jps_path_fixed(&map, scene.start_pos, scene.goal_pos).unwrap();
+        let json = serde_json::to_string(&path).unwrap();
+        assert_eq!(serde_json::from_str::<Route<Fixed>>(&json).unwrap(), path);
+
+        let mut options = SearchOptions::default();
+        options.tie_break = TieBreak::LowerH;
+        let json = serde_json::to_string(&options).unwrap();
+        assert_eq!(
+            serde_json::from_str::<SearchOptions>(&json).unwrap(),
+            options
+        );
+
+        //Precomputed data gives the same routes after loading
+        let grid = BitGrid::from_grid(&map);
+        let json = serde_json::to_string(&grid).unwrap();
+        assert_eq!(serde_json::from_str::<BitGrid>(&json).unwrap(), grid);
+
+        let graph = VisibilityGraph::from_grid(&map);
+        let json = serde_json::to_string(&graph).unwrap();
+        let loaded: VisibilityGraph = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            visibility_path(&loaded, scene.start_pos, scene.goal_pos),
+            visibility_path(&graph, scene.start_pos, scene.goal_pos)
+        );
+
+        let mesh = NavMesh::from_grid(&map);
+        let json = serde_json::to_string(&mesh).unwrap();
+        assert_eq!(serde_json::from_str::<NavMesh>(&json).unwrap(), mesh);
+
+        //Corrupted data is rejected when loading, rather than panicking when used
+        let mut small = BitGrid::new(70, 3);
+        small.set_passable((65, 1), true);
+        let mut value = serde_json::to_value(&small).unwrap();
+        value["rows"]["words"].as_array_mut().unwrap().pop();
+        assert!(serde_json::from_value::<BitGrid>(value).is_err());
+
+        let mut value = serde_json::to_value(&small).unwrap();
+        value["columns"]["words"][65] = serde_json::json!(0);
+        assert!(serde_json::from_value::<BitGrid>(value).is_err());
+
+        let mesh = NavMesh::new(
+            vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)],
+            vec![vec![0, 1, 2], vec![0, 2, 3]],
+        );
+        let mut value = serde_json::to_value(&mesh).unwrap();
+        value["polygons"][1][2] = serde_json::json!(4);
+        assert!(serde_json::from_value::<NavMesh>(value).is_err());
+
+        let mut value = serde_json::to_value(&graph).unwrap();
+        value["edges"][0][0][0] = serde_json::json!(usize::MAX);
+        assert!(serde_json::from_value::<VisibilityGraph>(value).is_err());
+
+        let mut value = serde_json::to_value(&graph).unwrap();
+        value["edges"].as_array_mut().unwrap().pop();
+        assert!(serde_json::from_value::<VisibilityGraph>(value).is_err());
+
+        let route = Route::from((2.0, vec![(2, 0), (1, 0), (0, 0)]));
+        let schedule = route.schedule(|_| 1.0).unwrap();
+        let json = serde_json::to_string(&schedule).unwrap();
+        assert_eq!(serde_json::from_str::<Schedule>(&json).unwrap(), schedule);
+
+        let mut value = serde_json::to_value(&schedule).unwrap();
+        value["halfway"].as_array_mut().unwrap().pop();
+        assert!(serde_json::from_value::<Schedule>(value).is_err());
+
+        let mut value = serde_json::to_value(&schedule).unwrap();
+        value["halfway"][0] = serde_json::json!(5.0);
+        assert!(serde_json::from_value::<Schedule>(value).is_err());
+
+        let spline = path.smooth(&map);
+        let json = serde_json::to_string(&spline).unwrap();
+        assert_eq!(serde_json::from_str::<Spline>(&json).unwrap(), spline);
+        let empty = Route::from((0.0, Vec::new()));
+        let json = serde_json::to_string(&empty.smooth(&map)).unwrap();
+        assert!(serde_json::from_str::<Spline>(&json).is_ok());
+
+        let mut value = serde_json::to_value(&spline).unwrap();
+        let samples = value["samples"].as_array_mut().unwrap();
+        let last = samples.len() - 1;
+        samples[last][0] = serde_json::json!(1e12);
+        assert!(serde_json::from_value::<Spline>(value).is_err());
+
+        let mut value = serde_json::to_value(&spline).unwrap();
+        value["samples"].as_array_mut().unwrap().swap(1, 2);
+        assert!(serde_json::from_value::<Spline>(value).is_err());
+
+        let mut value = serde_json::to_value(&spline).unwrap();
+        value["controls"] = serde_json::json!([]);
+        assert!(serde_json::from_value::<Spline>(value).is_err());
+
+        #[cfg(feature = "petgraph")]
+        {
+            use blitz_path::petgraph::Landmarks;
+            use petgraph::graph::{DiGraph, NodeIndex};
+
+            let graph = DiGraph::<(), f64>::from_edges([(0, 1, 1.0), (1, 2, 1.0), (2, 0, 1.0)]);
+            let landmarks = Landmarks::new(&graph, &[NodeIndex::new(1)]).unwrap();
+            let json = serde_json::to_string(&landmarks).unwrap();
+            assert_eq!(
+                serde_json::from_str::<Landmarks<f64>>(&json).unwrap(),
+                landmarks
+            );
+
+            let mut value = serde_json::to_value(&landmarks).unwrap();
+            value["to"] = serde_json::json!([]);
+            assert!(serde_json::from_value::<Landmarks<f64>>(value).is_err());
+
+            let mut value = serde_json::to_value(&landmarks).unwrap();
+            value["from"][0].as_array_mut().unwrap().pop();
+            assert!(serde_json::from_value::<Landmarks<f64>>(value).is_err());
+
+            let mut value = serde_json::to_value(&landmarks).unwrap();
+            value["from"][0][2] = serde_json::json!(-1.0);
+            assert!(serde_json::from_value::<Landmarks<f64>>(value).is_err());
+        }
+    }
+
+    #[cfg(feature = "petgraph")]
+    #[test]
+    fn petgraph() {
+        use blitz_path::petgraph::{a_star, bidirectional, landmark, Landmarks};
+        use petgraph::graph::{DiGraph, NodeIndex, UnGraph};
+        use petgraph::stable_graph::StableGraph;
+        use std::collections::HashMap;
+
+        let map = parse_map_file(Path::new(MAP)).unwrap();
+        let scenes = parse_scen_file(Path::new(SCEN)).unwrap();
+        let options = SearchOptions::default();
+
+        //Build a graph with a node for each passable tile
+        let mut graph = UnGraph::<Coords2D, f64>::new_undirected();
+        let mut nodes = HashMap::new();
+        for y in 0..map.height() {
+            for x in 0..map.width() {
+                if map.is_passable((x, y)) {
+                    nodes.insert((x, y), graph.add_node((x, y)));
+                }
+            }
+        }
+        for (&tile, &node) in nodes.iter() {
+            for next in map.neighbours(tile) {
+                if tile < next {
+                    graph.add_edge(node, nodes[&next], f64::distance(tile, next));
+                }
+            }
+        }
+
+        let first = NodeIndex::new(0);
+        let last = NodeIndex::new(graph.node_count() - 1);
+        let landmarks = Landmarks::new(&graph, &[first, last]).unwrap();
+
+        for index in TEST_NUMS_JPS.iter() {
+            let scene = &scenes[*index];
+            let (start, goal) = (nodes[&scene.start_pos], nodes[&scene.goal_pos]);
+            let heuristic = |node: NodeIndex| f64::distance(graph[node], scene.goal_pos);
+
+            let paths = [
+                a_star(&graph, start, goal, heuristic, &options).unwrap(),
+                bidirectional(&graph, start, goal, &options).unwrap(),
+                landmark(&graph, &landmarks, start, goal, &options).unwrap(),
+            ];
+
+            for path in paths.iter() {
+                assert!(
+                    (scene.optimal_length - path.distance()).abs() < 1e-6,
+                    "Test #{}",
+                    index
+                );
+                assert_eq!(path.steps()[0], goal, "Test #{}", index);
+                assert_eq!(path.steps().last(), Some(&start), "Test #{}", index);
+            }
+        }
+
+        //Directed edges can only be followed one way
+        let graph = DiGraph::<(), u32>::from_edges([(0, 1, 1), (1, 2, 1), (2, 0, 1)]);
+        let (start, goal) = (NodeIndex::new(0), NodeIndex::new(2));
+        let landmarks = Landmarks::new(&graph, &[NodeIndex::new(1)]).unwrap();
+
+        let paths = [
+            a_star(&graph, goal, start, |_| 0, &options).unwrap(),
+            bidirectional(&graph, goal, start, &options).unwrap(),
+            landmark(&graph, &landmarks, goal, start, &options).unwrap(),
+        ];
+        for path in paths.iter() {
+            assert_eq!(path.distance(), 1);
+            assert_eq!(path.steps(), vec![start, goal]);
+        }
+
+        let path = bidirectional(&graph, start, goal, &options).unwrap();
+        assert_eq!(path.distance(), 2);
+        assert_eq!(path.steps(), vec![goal, NodeIndex::new(1), start]);
+
+        //Unconnected nodes have no route and edge weights must be valid
+        let mut graph = DiGraph::<(), f64>::from_edges([(0, 1, 1.0), (2, 3, -1.0)]);
+        let isolated = graph.add_node(());
+        let (start, goal) = (NodeIndex::new(0), NodeIndex::new(2));
+
+        let path = bidirectional(&graph, start, isolated, &options);
+        assert_eq!(path.err(), Some(PathError::NoPath));
+        let path = bidirectional(&graph, start, NodeIndex::new(3), &options);
+        assert_eq!(path.err(), Some(PathError::InvalidCost));
+        let path = a_star(&graph, goal, NodeIndex::new(3), |_| 0.0, &options);
+        assert_eq!(path.err(), Some(PathError::InvalidCost));
+
+        //Stable graphs keep their indices when nodes are removed
+        let mut graph =
+            StableGraph::<(), u32>::from_edges([(0, 1, 1), (1, 2, 1), (0, 3, 1), (3, 2, 1)]);
+        graph.remove_node(NodeIndex::new(1));
+        let (start, goal) = (NodeIndex::new(0), NodeIndex::new(2));
+
+        let path = bidirectional(&graph, start, goal, &options).unwrap();
+        assert_eq!(path.steps(), vec![goal, NodeIndex::new(3), start]);
+    }
 }